@@ -0,0 +1,702 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use anchor_spl::token::spl_token::native_mint;
+use tag_the_chart_program::path::{OpenPriceGuard, SpendLimits};
+use tag_the_chart_program::{accounts, instruction};
+
+use crate::pda::{program_config_address, trading_account_address};
+use crate::pool::PoolAccounts;
+use crate::{MEMO_PROGRAM_ID, PROGRAM_ID, RAYDIUM_CLMM_PROGRAM_ID};
+
+/// Per-step arguments of a candle path
+///
+/// Every vector has one entry per step; `tolerances_bps` and `exact_outputs`
+/// may be left empty to use the program defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapPath {
+    pub target_sqrt_prices: Vec<u128>,
+    pub max_inputs: Vec<u64>,
+    pub min_outputs: Vec<u64>,
+    pub tolerances_bps: Vec<u16>,
+    pub exact_outputs: Vec<bool>,
+}
+
+impl SwapPath {
+    /// Exact-input path with no input or output limits
+    pub fn new(target_sqrt_prices: Vec<u128>) -> Self {
+        let len = target_sqrt_prices.len();
+        Self {
+            target_sqrt_prices,
+            max_inputs: vec![0; len],
+            min_outputs: vec![0; len],
+            tolerances_bps: Vec::new(),
+            exact_outputs: Vec::new(),
+        }
+    }
+}
+
+/// Build `initialize` for `owner`'s trading account `id`
+pub fn initialize(owner: &Pubkey, id: u16) -> Instruction {
+    let accounts = accounts::Initialize {
+        user: *owner,
+        trading_account: trading_account_address(owner, id).0,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::Initialize { id }.data(),
+    }
+}
+
+/// Build `migrate_trading_account` upgrading `owner`'s legacy trading account
+/// (always account 0) to the current layout
+pub fn migrate_trading_account(owner: &Pubkey) -> Instruction {
+    let accounts = accounts::MigrateTradingAccount {
+        user: *owner,
+        trading_account: trading_account_address(owner, 0).0,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::MigrateTradingAccount {}.data(),
+    }
+}
+
+/// Build `deposit` from `owner`'s ATA into trading account `id`'s ATA for `mint`
+pub fn deposit(
+    owner: &Pubkey,
+    id: u16,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let trading_account = trading_account_address(owner, id).0;
+    let accounts = accounts::Deposit {
+        user: *owner,
+        trading_account,
+        user_token_account: get_associated_token_address_with_program_id(
+            owner,
+            mint,
+            token_program,
+        ),
+        pda_token_account: get_associated_token_address_with_program_id(
+            &trading_account,
+            mint,
+            token_program,
+        ),
+        mint: *mint,
+        token_program: *token_program,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::Deposit { id, amount }.data(),
+    }
+}
+
+/// Build `deposit_sol` wrapping `lamports` into trading account `id`'s WSOL ATA
+pub fn deposit_sol(owner: &Pubkey, id: u16, lamports: u64) -> Instruction {
+    let trading_account = trading_account_address(owner, id).0;
+    let accounts = accounts::DepositSol {
+        user: *owner,
+        trading_account,
+        pda_wsol_account: get_associated_token_address_with_program_id(
+            &trading_account,
+            &native_mint::ID,
+            &anchor_spl::token::ID,
+        ),
+        wsol_mint: native_mint::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::DepositSol {
+            id,
+            amount: lamports,
+        }
+        .data(),
+    }
+}
+
+/// Build `withdraw` from trading account `id`'s ATA back to `owner`'s ATA for `mint`
+pub fn withdraw(
+    owner: &Pubkey,
+    id: u16,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let trading_account = trading_account_address(owner, id).0;
+    let accounts = accounts::Withdraw {
+        user: *owner,
+        trading_account,
+        pda_token_account: get_associated_token_address_with_program_id(
+            &trading_account,
+            mint,
+            token_program,
+        ),
+        user_token_account: get_associated_token_address_with_program_id(
+            owner,
+            mint,
+            token_program,
+        ),
+        mint: *mint,
+        token_program: *token_program,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::Withdraw { id, amount }.data(),
+    }
+}
+
+/// Build `close_trading_account` for `owner`'s trading account `id`
+///
+/// `token_accounts` lists the `(mint, token_program)` of every ATA the trading
+/// account holds; each is withdrawn to `owner`'s ATA, or unwrapped to the
+/// wallet for WSOL, and closed. The ATA of every mint in the ledger must be
+/// listed, even if empty; token accounts at other addresses can be appended to
+/// the returned instruction as further `(token_account, mint, destination)`
+/// triples.
+pub fn close_trading_account(
+    owner: &Pubkey,
+    id: u16,
+    token_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let trading_account = trading_account_address(owner, id).0;
+    let accounts = accounts::CloseTradingAccount {
+        user: *owner,
+        trading_account,
+        token_program: anchor_spl::token::ID,
+        token_program_2022: anchor_spl::token_2022::ID,
+    };
+
+    let mut metas = accounts.to_account_metas(None);
+    for (mint, token_program) in token_accounts {
+        let destination = if *mint == native_mint::ID {
+            *owner
+        } else {
+            get_associated_token_address_with_program_id(owner, mint, token_program)
+        };
+        metas.extend([
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&trading_account, mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(destination, false),
+        ]);
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: metas,
+        data: instruction::CloseTradingAccount { id }.data(),
+    }
+}
+
+/// Build `rebuild_ledger` adding the mints of `token_accounts` to the ledger
+/// of `owner`'s trading account `id`
+///
+/// `token_accounts` must list every token account the trading account holds.
+pub fn rebuild_ledger(owner: &Pubkey, id: u16, token_accounts: &[Pubkey]) -> Instruction {
+    let accounts = accounts::RebuildLedger {
+        user: *owner,
+        trading_account: trading_account_address(owner, id).0,
+    };
+
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(
+        token_accounts
+            .iter()
+            .map(|token_account| AccountMeta::new_readonly(*token_account, false)),
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: metas,
+        data: instruction::RebuildLedger { id }.data(),
+    }
+}
+
+/// Build `configure_pnl_pair` tracking `base_mint` in `quote_mint` terms
+/// for `owner`'s trading account `id`
+pub fn configure_pnl_pair(
+    owner: &Pubkey,
+    id: u16,
+    base_mint: &Pubkey,
+    quote_mint: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::ConfigurePnlPair {
+        user: *owner,
+        trading_account: trading_account_address(owner, id).0,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::ConfigurePnlPair {
+            id,
+            base_mint: *base_mint,
+            quote_mint: *quote_mint,
+        }
+        .data(),
+    }
+}
+
+/// Build `add_operator` letting `operator` swap for `owner`'s trading account `id`
+///
+/// `expires_at` is a unix timestamp; 0 never expires.
+pub fn add_operator(owner: &Pubkey, id: u16, operator: &Pubkey, expires_at: i64) -> Instruction {
+    let accounts = accounts::AddOperator {
+        user: *owner,
+        trading_account: trading_account_address(owner, id).0,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::AddOperator {
+            id,
+            operator: *operator,
+            expires_at,
+        }
+        .data(),
+    }
+}
+
+/// Build `remove_operator` revoking `operator` from `owner`'s trading account `id`
+pub fn remove_operator(owner: &Pubkey, id: u16, operator: &Pubkey) -> Instruction {
+    let accounts = accounts::RemoveOperator {
+        user: *owner,
+        trading_account: trading_account_address(owner, id).0,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::RemoveOperator {
+            id,
+            operator: *operator,
+        }
+        .data(),
+    }
+}
+
+/// Build `swap_to_prices_simple` for `wallet` on `pool`
+///
+/// `tick_arrays` are appended as writable remaining accounts, in order; see
+/// `PoolAccounts::tick_arrays`. Pass an `open_guard` built from the price the
+/// path was planned from to abort if the pool moved in the meantime, and
+/// `spend_limits` to cap the net cost of the whole path.
+pub fn swap_to_prices_simple(
+    wallet: &Pubkey,
+    pool: &PoolAccounts,
+    tick_arrays: &[Pubkey],
+    path: SwapPath,
+    unwrap_sol: bool,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Instruction {
+    let accounts = accounts::SwapCandleSimple {
+        wallet: *wallet,
+        token_account_a: get_associated_token_address_with_program_id(
+            wallet,
+            &pool.token_mint_a,
+            &pool.token_program_a,
+        ),
+        token_account_b: get_associated_token_address_with_program_id(
+            wallet,
+            &pool.token_mint_b,
+            &pool.token_program_b,
+        ),
+        raydium_program: RAYDIUM_CLMM_PROGRAM_ID,
+        program_config: program_config_address().0,
+        amm_config: pool.amm_config,
+        pool_state: pool.pool,
+        token_vault_a: pool.token_vault_a,
+        token_vault_b: pool.token_vault_b,
+        token_mint_a: pool.token_mint_a,
+        token_mint_b: pool.token_mint_b,
+        observation_state: pool.observation_state,
+        token_program: anchor_spl::token::ID,
+        token_program_2022: anchor_spl::token_2022::ID,
+        memo_program: MEMO_PROGRAM_ID,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    };
+
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(
+        tick_arrays
+            .iter()
+            .map(|tick_array| AccountMeta::new(*tick_array, false)),
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: metas,
+        data: instruction::SwapToPricesSimple {
+            target_sqrt_prices: path.target_sqrt_prices,
+            max_inputs: path.max_inputs,
+            min_outputs: path.min_outputs,
+            tolerances_bps: path.tolerances_bps,
+            exact_outputs: path.exact_outputs,
+            unwrap_sol,
+            open_guard,
+            spend_limits,
+        }
+        .data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    /// Instruction data: the discriminator followed by the borsh-encoded args
+    fn data(discriminator: &[u8], args: &[&[u8]]) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        for arg in args {
+            data.extend_from_slice(arg);
+        }
+        data
+    }
+
+    fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &anchor_spl::token::ID)
+    }
+
+    #[test]
+    fn initialize_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let ix = initialize(&owner, 3);
+
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account_address(&owner, 3).0, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::Initialize::DISCRIMINATOR,
+                &[&3u16.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn migrate_trading_account_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let ix = migrate_trading_account(&owner);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account_address(&owner, 0).0, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(instruction::MigrateTradingAccount::DISCRIMINATOR, &[])
+        );
+    }
+
+    #[test]
+    fn deposit_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let trading_account = trading_account_address(&owner, 1).0;
+        let ix = deposit(&owner, 1, &mint, &anchor_spl::token::ID, 500);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account, false),
+                AccountMeta::new(ata(&owner, &mint), false),
+                AccountMeta::new(ata(&trading_account, &mint), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::Deposit::DISCRIMINATOR,
+                &[&1u16.to_le_bytes(), &500u64.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn deposit_sol_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let trading_account = trading_account_address(&owner, 0).0;
+        let ix = deposit_sol(&owner, 0, 1_000);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account, false),
+                AccountMeta::new(ata(&trading_account, &native_mint::ID), false),
+                AccountMeta::new_readonly(native_mint::ID, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+                AccountMeta::new_readonly(associated_token::ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::DepositSol::DISCRIMINATOR,
+                &[&0u16.to_le_bytes(), &1_000u64.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn withdraw_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let trading_account = trading_account_address(&owner, 0).0;
+        let ix = withdraw(&owner, 0, &mint, &anchor_spl::token::ID, 7);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account, false),
+                AccountMeta::new(ata(&trading_account, &mint), false),
+                AccountMeta::new(ata(&owner, &mint), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::Withdraw::DISCRIMINATOR,
+                &[&0u16.to_le_bytes(), &7u64.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn close_trading_account_appends_one_triple_per_token_account() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let trading_account = trading_account_address(&owner, 2).0;
+        let ix = close_trading_account(
+            &owner,
+            2,
+            &[
+                (mint, anchor_spl::token::ID),
+                (native_mint::ID, anchor_spl::token::ID),
+            ],
+        );
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(trading_account, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+                AccountMeta::new_readonly(anchor_spl::token_2022::ID, false),
+                AccountMeta::new(ata(&trading_account, &mint), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(ata(&owner, &mint), false),
+                // WSOL is unwrapped straight to the wallet
+                AccountMeta::new(ata(&trading_account, &native_mint::ID), false),
+                AccountMeta::new_readonly(native_mint::ID, false),
+                AccountMeta::new(owner, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::CloseTradingAccount::DISCRIMINATOR,
+                &[&2u16.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn rebuild_ledger_lists_token_accounts_read_only() {
+        let owner = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let ix = rebuild_ledger(&owner, 0, &[token_account]);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(trading_account_address(&owner, 0).0, false),
+                AccountMeta::new_readonly(token_account, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::RebuildLedger::DISCRIMINATOR,
+                &[&0u16.to_le_bytes()]
+            )
+        );
+    }
+
+    #[test]
+    fn configure_pnl_pair_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        let ix = configure_pnl_pair(&owner, 0, &base, &quote);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(trading_account_address(&owner, 0).0, false),
+            ]
+        );
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::ConfigurePnlPair::DISCRIMINATOR,
+                &[&0u16.to_le_bytes(), base.as_ref(), quote.as_ref()]
+            )
+        );
+    }
+
+    #[test]
+    fn operator_accounts_and_data() {
+        let owner = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+        let accounts = vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(trading_account_address(&owner, 0).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ];
+
+        let add = add_operator(&owner, 0, &operator, 1_700_000_000);
+        assert_eq!(add.accounts, accounts);
+        assert_eq!(
+            add.data,
+            data(
+                instruction::AddOperator::DISCRIMINATOR,
+                &[
+                    &0u16.to_le_bytes(),
+                    operator.as_ref(),
+                    &1_700_000_000i64.to_le_bytes()
+                ]
+            )
+        );
+
+        let remove = remove_operator(&owner, 0, &operator);
+        assert_eq!(remove.accounts, accounts);
+        assert_eq!(
+            remove.data,
+            data(
+                instruction::RemoveOperator::DISCRIMINATOR,
+                &[&0u16.to_le_bytes(), operator.as_ref()]
+            )
+        );
+    }
+
+    #[test]
+    fn swap_to_prices_simple_accounts_and_data() {
+        let wallet = Pubkey::new_unique();
+        let pool = PoolAccounts {
+            pool: Pubkey::new_unique(),
+            amm_config: Pubkey::new_unique(),
+            token_mint_a: Pubkey::new_unique(),
+            token_mint_b: Pubkey::new_unique(),
+            token_vault_a: Pubkey::new_unique(),
+            token_vault_b: Pubkey::new_unique(),
+            observation_state: Pubkey::new_unique(),
+            token_program_a: anchor_spl::token::ID,
+            token_program_b: anchor_spl::token::ID,
+            tick_spacing: 10,
+            tick_current: 0,
+            sqrt_price_x64: 0,
+        };
+        let tick_array = Pubkey::new_unique();
+        let path = SwapPath {
+            target_sqrt_prices: vec![5],
+            max_inputs: vec![6],
+            min_outputs: vec![7],
+            tolerances_bps: Vec::new(),
+            exact_outputs: vec![true],
+        };
+
+        let ix = swap_to_prices_simple(&wallet, &pool, &[tick_array], path, true, None, None);
+
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(wallet, true),
+                AccountMeta::new(ata(&wallet, &pool.token_mint_a), false),
+                AccountMeta::new(ata(&wallet, &pool.token_mint_b), false),
+                AccountMeta::new_readonly(RAYDIUM_CLMM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(program_config_address().0, false),
+                AccountMeta::new_readonly(pool.amm_config, false),
+                AccountMeta::new(pool.pool, false),
+                AccountMeta::new(pool.token_vault_a, false),
+                AccountMeta::new(pool.token_vault_b, false),
+                AccountMeta::new_readonly(pool.token_mint_a, false),
+                AccountMeta::new_readonly(pool.token_mint_b, false),
+                AccountMeta::new(pool.observation_state, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+                AccountMeta::new_readonly(anchor_spl::token_2022::ID, false),
+                AccountMeta::new_readonly(MEMO_PROGRAM_ID, false),
+                AccountMeta::new_readonly(associated_token::ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(tick_array, false),
+            ]
+        );
+        // Vecs are a u32 length then the items; options a 0 tag when None
+        assert_eq!(
+            ix.data,
+            data(
+                instruction::SwapToPricesSimple::DISCRIMINATOR,
+                &[
+                    &1u32.to_le_bytes(),
+                    &5u128.to_le_bytes(),
+                    &1u32.to_le_bytes(),
+                    &6u64.to_le_bytes(),
+                    &1u32.to_le_bytes(),
+                    &7u64.to_le_bytes(),
+                    &0u32.to_le_bytes(),
+                    &1u32.to_le_bytes(),
+                    &[1],
+                    &[1],
+                    &[0],
+                    &[0],
+                ]
+            )
+        );
+    }
+}
@@ -0,0 +1,25 @@
+//! Rust client for the tag-the-chart program
+//!
+//! Derives the program's PDAs, resolves the Raydium CLMM accounts a swap
+//! needs from a `PoolState`, and builds the program's instructions with
+//! their account metas.
+
+mod instructions;
+mod pda;
+mod pool;
+
+pub use instructions::*;
+pub use pda::*;
+pub use pool::*;
+
+pub use tag_the_chart_program::path::{OpenPriceGuard, SpendLimits};
+pub use tag_the_chart_program::ID as PROGRAM_ID;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::pubkey;
+
+/// Raydium CLMM program
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey = pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+
+/// SPL Memo program, required by Raydium `swap_v2`
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::Pubkey;
+use raydium_amm_v3::states::{POOL_TICK_ARRAY_BITMAP_SEED, TICK_ARRAY_SEED};
+use tag_the_chart_program::state::TradingAccount;
+
+use crate::{PROGRAM_ID, RAYDIUM_CLMM_PROGRAM_ID};
+
+/// Trading account `id` of `owner`
+/// Seeds: ["trading_account", owner, id (little endian, empty for 0)]
+pub fn trading_account_address(owner: &Pubkey, id: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"trading_account",
+            owner.as_ref(),
+            &TradingAccount::id_seed(id),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// Program config PDA holding the pool allowlist
+/// Seeds: ["program_config"]
+pub fn program_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"program_config"], &PROGRAM_ID)
+}
+
+/// Raydium tick array starting at `start_tick_index`
+/// Seeds: ["tick_array", pool, start_tick_index (big endian)]
+pub fn tick_array_address(pool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            TICK_ARRAY_SEED.as_bytes(),
+            pool.as_ref(),
+            &start_tick_index.to_be_bytes(),
+        ],
+        &RAYDIUM_CLMM_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Raydium tick array bitmap extension of `pool`
+/// Seeds: ["pool_tick_array_bitmap_extension", pool]
+pub fn tick_array_bitmap_extension_address(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(), pool.as_ref()],
+        &RAYDIUM_CLMM_PROGRAM_ID,
+    )
+    .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trading_account_zero_keeps_original_seeds() {
+        let owner = Pubkey::new_unique();
+        let original =
+            Pubkey::find_program_address(&[b"trading_account", owner.as_ref()], &PROGRAM_ID);
+
+        assert_eq!(trading_account_address(&owner, 0), original);
+    }
+
+    #[test]
+    fn trading_sub_accounts_append_little_endian_id() {
+        let owner = Pubkey::new_unique();
+        let id: u16 = 258;
+        let expected = Pubkey::find_program_address(
+            &[b"trading_account", owner.as_ref(), &id.to_le_bytes()],
+            &PROGRAM_ID,
+        );
+
+        assert_eq!(trading_account_address(&owner, id), expected);
+        assert_ne!(
+            trading_account_address(&owner, id).0,
+            trading_account_address(&owner, 0).0
+        );
+    }
+}
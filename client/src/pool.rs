@@ -0,0 +1,145 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Result};
+use raydium_amm_v3::states::{PoolState, TickArrayState};
+
+use crate::pda::tick_array_address;
+
+/// Raydium CLMM accounts a swap needs, resolved from a `PoolState`
+///
+/// Token A and B follow the pool's mint order (`token_mint_0`/`token_mint_1`).
+/// `PoolState` does not record which token program owns each mint, so both
+/// default to SPL Token; use `with_token_programs` for Token-2022 mints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolAccounts {
+    pub pool: Pubkey,
+    pub amm_config: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_vault_b: Pubkey,
+    pub observation_state: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub tick_spacing: u16,
+    pub tick_current: i32,
+    pub sqrt_price_x64: u128,
+}
+
+impl PoolAccounts {
+    pub fn from_pool_state(pool: Pubkey, pool_state: &PoolState) -> Self {
+        // PoolState is packed, so read every field by value
+        Self {
+            pool,
+            amm_config: pool_state.amm_config,
+            token_mint_a: pool_state.token_mint_0,
+            token_mint_b: pool_state.token_mint_1,
+            token_vault_a: pool_state.token_vault_0,
+            token_vault_b: pool_state.token_vault_1,
+            observation_state: pool_state.observation_key,
+            token_program_a: anchor_spl::token::ID,
+            token_program_b: anchor_spl::token::ID,
+            tick_spacing: pool_state.tick_spacing,
+            tick_current: pool_state.tick_current,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+        }
+    }
+
+    /// Resolve the pool accounts from the raw data of the pool account
+    pub fn from_account_data(pool: Pubkey, data: &[u8]) -> Result<Self> {
+        let pool_state = PoolState::try_deserialize(&mut &data[..])?;
+        Ok(Self::from_pool_state(pool, &pool_state))
+    }
+
+    /// Set the token program that owns each mint
+    pub fn with_token_programs(mut self, token_program_a: Pubkey, token_program_b: Pubkey) -> Self {
+        self.token_program_a = token_program_a;
+        self.token_program_b = token_program_b;
+        self
+    }
+
+    /// Start tick index of the tick array holding the current tick
+    pub fn current_tick_array_start_index(&self) -> i32 {
+        TickArrayState::get_array_start_index(self.tick_current, self.tick_spacing)
+    }
+
+    /// The current tick array followed by the next `count - 1` arrays in the
+    /// swap direction, in the order Raydium crosses them
+    ///
+    /// Addresses are derived, not fetched: drop any array that does not
+    /// exist on chain before passing them to a swap, since Raydium expects
+    /// every listed tick array to be initialized.
+    pub fn tick_arrays(&self, a_for_b: bool, count: usize) -> Vec<Pubkey> {
+        let ticks_per_array = TickArrayState::tick_count(self.tick_spacing);
+        let step = if a_for_b {
+            -ticks_per_array
+        } else {
+            ticks_per_array
+        };
+        let start = self.current_tick_array_start_index();
+
+        (0..count as i32)
+            .map(|i| tick_array_address(&self.pool, start + step * i))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_at_tick(tick_current: i32) -> PoolAccounts {
+        PoolAccounts {
+            pool: Pubkey::new_unique(),
+            amm_config: Pubkey::new_unique(),
+            token_mint_a: Pubkey::new_unique(),
+            token_mint_b: Pubkey::new_unique(),
+            token_vault_a: Pubkey::new_unique(),
+            token_vault_b: Pubkey::new_unique(),
+            observation_state: Pubkey::new_unique(),
+            token_program_a: anchor_spl::token::ID,
+            token_program_b: anchor_spl::token::ID,
+            // TICK_ARRAY_SIZE (60) * tick_spacing (10): one array spans 600 ticks
+            tick_spacing: 10,
+            tick_current,
+            sqrt_price_x64: 0,
+        }
+    }
+
+    #[test]
+    fn current_tick_array_rounds_towards_negative_infinity() {
+        assert_eq!(pool_at_tick(0).current_tick_array_start_index(), 0);
+        assert_eq!(pool_at_tick(599).current_tick_array_start_index(), 0);
+        assert_eq!(pool_at_tick(600).current_tick_array_start_index(), 600);
+        assert_eq!(pool_at_tick(-1).current_tick_array_start_index(), -600);
+        assert_eq!(pool_at_tick(-600).current_tick_array_start_index(), -600);
+        assert_eq!(pool_at_tick(-601).current_tick_array_start_index(), -1200);
+    }
+
+    #[test]
+    fn tick_arrays_walk_down_when_selling_a() {
+        let pool = pool_at_tick(-5);
+
+        assert_eq!(
+            pool.tick_arrays(true, 3),
+            vec![
+                tick_array_address(&pool.pool, -600),
+                tick_array_address(&pool.pool, -1200),
+                tick_array_address(&pool.pool, -1800),
+            ]
+        );
+    }
+
+    #[test]
+    fn tick_arrays_walk_up_when_selling_b() {
+        let pool = pool_at_tick(1234);
+
+        assert_eq!(
+            pool.tick_arrays(false, 3),
+            vec![
+                tick_array_address(&pool.pool, 1200),
+                tick_array_address(&pool.pool, 1800),
+                tick_array_address(&pool.pool, 2400),
+            ]
+        );
+    }
+}
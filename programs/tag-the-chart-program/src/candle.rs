@@ -0,0 +1,145 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Raydium expresses trade fee rates in hundredths of a basis point
+const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+/// Accumulates what a candle execution did, one path step at a time
+pub struct CandleTracker {
+    open_sqrt_price: u128,
+    reached_sqrt_prices: Vec<u128>,
+    high_sqrt_price: u128,
+    low_sqrt_price: u128,
+    trade_fee_rate: u32,
+    total_in_a: u64,
+    total_out_a: u64,
+    total_in_b: u64,
+    total_out_b: u64,
+    estimated_fee_a: u64,
+    estimated_fee_b: u64,
+}
+
+impl CandleTracker {
+    pub fn new(open_sqrt_price: u128, trade_fee_rate: u32, steps: usize) -> Self {
+        Self {
+            open_sqrt_price,
+            reached_sqrt_prices: Vec::with_capacity(steps),
+            high_sqrt_price: open_sqrt_price,
+            low_sqrt_price: open_sqrt_price,
+            trade_fee_rate,
+            total_in_a: 0,
+            total_out_a: 0,
+            total_in_b: 0,
+            total_out_b: 0,
+            estimated_fee_a: 0,
+            estimated_fee_b: 0,
+        }
+    }
+
+    /// Record the price a step ended at
+    ///
+    /// Each step moves the price in one direction only, so the step end
+    /// points are enough to find the high and low of the whole candle.
+    pub fn record_price(&mut self, sqrt_price: u128) {
+        self.reached_sqrt_prices.push(sqrt_price);
+        self.high_sqrt_price = self.high_sqrt_price.max(sqrt_price);
+        self.low_sqrt_price = self.low_sqrt_price.min(sqrt_price);
+    }
+
+    /// Record the amounts a step actually moved, from the `(a, b)` token
+    /// balances around its swap
+    ///
+    /// `wrapped` is anything added to the input account outside the pool
+    /// during the step (SOL wrapped on the fly). The trade fee is charged on
+    /// the input side, so it is estimated from the input and the pool fee
+    /// rate, rounded up. Raydium rounds the fee of every tick it crosses
+    /// instead, so its actual fee can differ by a few units.
+    pub fn record_swap(
+        &mut self,
+        a_for_b: bool,
+        before: (u64, u64),
+        after: (u64, u64),
+        wrapped: u64,
+    ) -> Result<()> {
+        let (input_before, input_after, output_before, output_after) = if a_for_b {
+            (before.0, after.0, before.1, after.1)
+        } else {
+            (before.1, after.1, before.0, after.0)
+        };
+        let amount_in = input_before
+            .saturating_add(wrapped)
+            .saturating_sub(input_after);
+        let amount_out = output_after.saturating_sub(output_before);
+        let fee = (u128::from(amount_in) * u128::from(self.trade_fee_rate))
+            .div_ceil(FEE_RATE_DENOMINATOR) as u64;
+
+        let (total_in, fee_total, total_out) = if a_for_b {
+            (
+                &mut self.total_in_a,
+                &mut self.estimated_fee_a,
+                &mut self.total_out_b,
+            )
+        } else {
+            (
+                &mut self.total_in_b,
+                &mut self.estimated_fee_b,
+                &mut self.total_out_a,
+            )
+        };
+        *total_in = total_in.checked_add(amount_in).ok_or(ErrorCode::Overflow)?;
+        *fee_total = fee_total.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+        *total_out = total_out
+            .checked_add(amount_out)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Build the summary event for this execution
+    pub fn finish(self, pool: Pubkey, signer: Pubkey) -> CandleExecuted {
+        CandleExecuted {
+            pool,
+            signer,
+            open_sqrt_price: self.open_sqrt_price,
+            reached_sqrt_prices: self.reached_sqrt_prices,
+            high_sqrt_price: self.high_sqrt_price,
+            low_sqrt_price: self.low_sqrt_price,
+            total_in_a: self.total_in_a,
+            total_out_a: self.total_out_a,
+            total_in_b: self.total_in_b,
+            total_out_b: self.total_out_b,
+            estimated_fee_a: self.estimated_fee_a,
+            estimated_fee_b: self.estimated_fee_b,
+        }
+    }
+}
+
+/// One record per candle execution, emitted after the last path step
+#[event]
+pub struct CandleExecuted {
+    pub pool: Pubkey,
+    /// Wallet that signed the execution
+    pub signer: Pubkey,
+    /// Pool sqrt price before the first step
+    pub open_sqrt_price: u128,
+    /// Pool sqrt price after each step, in path order
+    pub reached_sqrt_prices: Vec<u128>,
+    pub high_sqrt_price: u128,
+    pub low_sqrt_price: u128,
+    /// Token A sent into the pool, fees included
+    pub total_in_a: u64,
+    /// Token A received from the pool
+    pub total_out_a: u64,
+    /// Token B sent into the pool, fees included
+    pub total_in_b: u64,
+    /// Token B received from the pool
+    pub total_out_b: u64,
+    /// Trade fees paid in token A, estimated per step as
+    /// `ceil(input * trade_fee_rate / 1e6)`
+    ///
+    /// Raydium does not report the fee it charged to the caller, so this can
+    /// differ from it by the per-tick rounding. `total_in_a` is exact.
+    pub estimated_fee_a: u64,
+    /// Trade fees paid in token B, estimated like `estimated_fee_a`
+    pub estimated_fee_b: u64,
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+/// Program-wide error codes
+///
+/// Anchor numbers the variants from 6000 in declaration order. The enum has
+/// not shipped in a release yet, so codes may still be removed and the rest
+/// renumbered; every such change is listed in CHANGELOG.md. Once released,
+/// only ever append new variants at the end.
+#[error_code]
+pub enum ErrorCode {
+    /// 6000
+    #[msg("Unauthorized: You don't own this trading account")]
+    Unauthorized,
+    /// 6001
+    #[msg("Amount must be greater than 0")]
+    InvalidAmount,
+    /// 6002
+    #[msg("Insufficient balance in trading account")]
+    InsufficientBalance,
+    /// 6003
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    /// 6004
+    #[msg("Invalid token program: must be SPL Token or Token-2022")]
+    InvalidTokenProgram,
+    /// 6005
+    #[msg("Invalid token account owner: must be owned by the token program")]
+    InvalidTokenAccountOwner,
+    /// 6006
+    #[msg("Invalid token account: mint does not match the pool token mint")]
+    InvalidTokenMint,
+    /// 6007
+    #[msg("Price not reached: swap did not reach target price within tolerance")]
+    PriceNotReached,
+    /// 6008
+    #[msg("Empty path: at least one target price is required")]
+    EmptyPath,
+    /// 6009
+    #[msg("Path length mismatch: every per-step argument must have one entry per target")]
+    PathLengthMismatch,
+    /// 6010
+    #[msg("Invalid token ATA: must be the wallet's associated token account for the pool mint")]
+    InvalidTokenAta,
+    /// 6011
+    #[msg("Invalid tolerance: must be at most 10000 basis points")]
+    InvalidTolerance,
+    /// 6012
+    #[msg("Invalid exact output: exact-output steps need a non-zero output amount")]
+    InvalidExactOutput,
+    /// 6013
+    #[msg("Exact output not filled: price limit reached before the requested output")]
+    ExactOutputNotFilled,
+    /// 6014
+    #[msg(
+        "Price conversion overflow: decimal price does not fit once scaled by the mint decimals"
+    )]
+    PriceConversionOverflow,
+    /// 6015
+    #[msg("Price out of range: price must be non-zero and within the pool's tick range")]
+    PriceOutOfRange,
+    /// 6016
+    #[msg("Tick out of range: target tick must be within MIN_TICK and MAX_TICK")]
+    TickOutOfRange,
+    /// 6017
+    #[msg("Tick not aligned: target tick must be a multiple of the pool's tick spacing")]
+    TickNotAligned,
+    /// 6018
+    #[msg("Invalid offset: price offsets must be greater than -10000 basis points")]
+    InvalidOffset,
+    /// 6019
+    #[msg(
+        "Open price drift: pool moved too far from the expected open price before the first step"
+    )]
+    OpenPriceDrift,
+    /// 6020
+    #[msg("Net spend exceeded: the path cost more base or quote than allowed")]
+    NetSpendExceeded,
+    /// 6021
+    #[msg("Pool not allowed: the pool is not on the program config allowlist")]
+    PoolNotAllowed,
+    /// 6022
+    #[msg("Pool allowlist full: remove a pool before adding another")]
+    PoolAllowlistFull,
+    /// 6023
+    #[msg("Pool already allowed: the pool is already on the allowlist")]
+    PoolAlreadyAllowed,
+    /// 6024
+    #[msg("Invalid amm config: must be the pool state's amm_config")]
+    InvalidAmmConfig,
+    /// 6025
+    #[msg("Invalid token vault: must be the pool state's token_vault_0 and token_vault_1")]
+    InvalidTokenVault,
+    /// 6026
+    #[msg(
+        "Invalid pool mint: mint A and B must be the pool state's token_mint_0 and token_mint_1"
+    )]
+    InvalidPoolMint,
+    /// 6027
+    #[msg("Invalid observation state: must be the pool state's observation_key")]
+    InvalidObservationState,
+    /// 6028
+    #[msg("Swaps are paused")]
+    ProgramPaused,
+    /// 6029
+    #[msg("Operator expiry must be 0 (never) or in the future")]
+    InvalidOperatorExpiry,
+    /// 6030
+    #[msg("Key is already the owner or an operator of this trading account")]
+    OperatorAlreadyAdded,
+    /// 6031
+    #[msg("Trading account has the maximum number of operators")]
+    TooManyOperators,
+    /// 6032
+    #[msg("Operator not found")]
+    OperatorNotFound,
+    /// 6033
+    #[msg("Remaining accounts must come in (pda_token_account, mint, destination) triples")]
+    InvalidRemainingAccounts,
+    /// 6034
+    #[msg("Token account still holds a balance that would be stranded")]
+    BalanceStranded,
+    /// 6035
+    #[msg("Trading account already uses the current layout")]
+    AlreadyMigrated,
+    /// 6036
+    #[msg("PnL pair needs two different, non-default mints")]
+    InvalidPnlPair,
+    /// 6037
+    #[msg("Missing token account: every mint in the ledger needs its associated token account to close")]
+    MissingTokenAccount,
+    /// 6038
+    #[msg("Invalid WSOL destination: WSOL accounts are closed to the owner's wallet")]
+    InvalidWsolDestination,
+    /// 6039
+    #[msg("Trading account ledger has the maximum number of mints")]
+    TooManyLedgerMints,
+    /// 6040
+    #[msg("Trading account ledger must be rebuilt before the account can be closed")]
+    LedgerRebuildPending,
+    /// 6041
+    #[msg("Token account passed more than once")]
+    DuplicateTokenAccount,
+    /// 6042
+    #[msg("Trading account layout version is not supported by this program version")]
+    UnsupportedVersion,
+}
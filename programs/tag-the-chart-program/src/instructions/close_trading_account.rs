@@ -0,0 +1,186 @@
+use crate::errors::ErrorCode;
+use crate::state::TradingAccount;
+use crate::utils::token_balance;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::{spl_token::native_mint, Token};
+use anchor_spl::token_interface::{self, Mint, Token2022, TokenAccount};
+
+/// Close trading account `id`, returning every balance and all rent to the owner
+///
+/// `remaining_accounts` holds one `(pda_token_account, mint, destination)`
+/// triple per token account owned by the trading account. The balance is
+/// withdrawn to `destination` (the owner's token account for that mint) and
+/// the token account is closed. WSOL accounts are closed straight to the
+/// owner's wallet, which unwraps their balance; pass the owner's wallet as
+/// their `destination`. The whole instruction fails if any balance cannot be
+/// moved out, so nothing is left behind in a passed account.
+///
+/// The associated token account of every mint in the ledger must be among the
+/// triples, even if empty, so no mint the account ever held is left open.
+/// Deposits accept any token account owned by the trading account, so pass
+/// the other ones holding a balance as well; each account may appear once.
+/// A migrated account cannot be closed until `rebuild_ledger` has filled its
+/// ledger.
+pub fn close_trading_account<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseTradingAccount<'info>>,
+    id: u16,
+) -> Result<()> {
+    require!(
+        ctx.remaining_accounts.len() % 3 == 0,
+        ErrorCode::InvalidRemainingAccounts
+    );
+
+    require!(
+        !ctx.accounts.trading_account.ledger_rebuild_pending,
+        ErrorCode::LedgerRebuildPending
+    );
+
+    let triples: Vec<_> = ctx.remaining_accounts.chunks(3).collect();
+    for (index, accounts) in triples.iter().enumerate() {
+        require!(
+            !triples[..index]
+                .iter()
+                .any(|other| other[0].key == accounts[0].key),
+            ErrorCode::DuplicateTokenAccount
+        );
+    }
+
+    // Ownership and mint of each passed account are checked when it is closed
+    let trading_account_key = ctx.accounts.trading_account.key();
+    for entry in &ctx.accounts.trading_account.ledger {
+        let has_ata = triples.iter().any(|accounts| {
+            accounts[1].key() == entry.mint
+                && accounts[0].key()
+                    == get_associated_token_address_with_program_id(
+                        &trading_account_key,
+                        &entry.mint,
+                        accounts[0].owner,
+                    )
+        });
+        require!(has_ata, ErrorCode::MissingTokenAccount);
+    }
+
+    let user_key = ctx.accounts.user.key();
+    let bump = ctx.accounts.trading_account.bump;
+    let id_seed = TradingAccount::id_seed(id);
+
+    let seeds = &[b"trading_account", user_key.as_ref(), &id_seed, &[bump]];
+    let signer = &[&seeds[..]];
+
+    for accounts in triples {
+        close_token_account(&ctx, signer, &accounts[0], &accounts[1], &accounts[2])?;
+    }
+
+    // The trading account itself is closed to the owner by its `close` constraint
+    msg!(
+        "Trading account {} closed for user: {}",
+        id,
+        ctx.accounts.user.key()
+    );
+
+    Ok(())
+}
+
+/// Withdraw the balance of one PDA-owned token account and close it
+fn close_token_account<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CloseTradingAccount<'info>>,
+    signer: &[&[&[u8]]],
+    pda_token_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let token_program = if *pda_token_account.owner == ctx.accounts.token_program.key() {
+        ctx.accounts.token_program.to_account_info()
+    } else if *pda_token_account.owner == ctx.accounts.token_program_2022.key() {
+        ctx.accounts.token_program_2022.to_account_info()
+    } else {
+        return err!(ErrorCode::InvalidTokenProgram);
+    };
+
+    let token_account =
+        TokenAccount::try_deserialize(&mut &pda_token_account.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        token_account.owner,
+        ctx.accounts.trading_account.key(),
+        ErrorCode::InvalidTokenAccountOwner
+    );
+    require_keys_eq!(token_account.mint, mint.key(), ErrorCode::InvalidTokenMint);
+
+    let close_destination = if token_account.mint == native_mint::ID {
+        // Closing a WSOL account unwraps its whole balance along with the rent
+        require_keys_eq!(
+            destination.key(),
+            ctx.accounts.user.key(),
+            ErrorCode::InvalidWsolDestination
+        );
+        destination.clone()
+    } else {
+        if token_account.amount > 0 {
+            let mint_account = Mint::try_deserialize(&mut &mint.try_borrow_data()?[..])?;
+            let destination_account =
+                TokenAccount::try_deserialize(&mut &destination.try_borrow_data()?[..])?;
+            require_keys_eq!(
+                destination_account.owner,
+                ctx.accounts.user.key(),
+                ErrorCode::Unauthorized
+            );
+            require_keys_eq!(
+                destination_account.mint,
+                mint.key(),
+                ErrorCode::InvalidTokenMint
+            );
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    token_interface::TransferChecked {
+                        from: pda_token_account.clone(),
+                        mint: mint.clone(),
+                        to: destination.clone(),
+                        authority: ctx.accounts.trading_account.to_account_info(),
+                    },
+                    signer,
+                ),
+                token_account.amount,
+                mint_account.decimals,
+            )?;
+        }
+
+        require!(
+            token_balance(pda_token_account)? == 0,
+            ErrorCode::BalanceStranded
+        );
+        ctx.accounts.user.to_account_info()
+    };
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program,
+        token_interface::CloseAccount {
+            account: pda_token_account.clone(),
+            destination: close_destination,
+            authority: ctx.accounts.trading_account.to_account_info(),
+        },
+        signer,
+    ))
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct CloseTradingAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized,
+        close = user
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub token_program_2022: Program<'info, Token2022>,
+}
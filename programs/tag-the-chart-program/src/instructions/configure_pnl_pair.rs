@@ -0,0 +1,41 @@
+use crate::errors::ErrorCode;
+use crate::pnl::PnlPosition;
+use crate::state::TradingAccount;
+use anchor_lang::prelude::*;
+
+/// Track the cost basis and realized PnL of `base_mint`, valued in `quote_mint`
+///
+/// Starts from an empty position: the previous pair's figures are dropped.
+pub fn configure_pnl_pair(
+    ctx: Context<ConfigurePnlPair>,
+    _id: u16,
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+) -> Result<()> {
+    require!(
+        base_mint != quote_mint
+            && base_mint != Pubkey::default()
+            && quote_mint != Pubkey::default(),
+        ErrorCode::InvalidPnlPair
+    );
+
+    ctx.accounts.trading_account.pnl = PnlPosition::new(base_mint, quote_mint);
+
+    msg!("PnL pair set: base {}, quote {}", base_mint, quote_mint);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct ConfigurePnlPair<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+}
@@ -1,15 +1,16 @@
-use crate::state::TradingAccount;
+use crate::errors::ErrorCode;
+use crate::state::{LedgerMintSkipped, TradingAccount};
 use anchor_lang::prelude::*;
 use anchor_spl::token;
 use anchor_spl::token_2022;
 use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 
-/// Deposit tokens into PDA-owned token account
+/// Deposit tokens into the token account of trading account `id`
 ///
 /// This transfers tokens from user's wallet to a PDA-owned token account.
 /// Works with both SPL Token and Token-2022, including WSOL.
 /// Use this for depositing already-wrapped WSOL.
-pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+pub fn deposit(ctx: Context<Deposit>, _id: u16, amount: u64) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
 
     // Transfer tokens from user's token account to PDA-owned token account
@@ -27,17 +28,36 @@ pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         ctx.accounts.mint.decimals,
     )?;
 
+    let trading_account_key = ctx.accounts.trading_account.key();
+    let mint = ctx.accounts.mint.key();
+    match ctx.accounts.trading_account.ledger_mut(mint) {
+        Some(entry) => entry.record_deposit(amount)?,
+        None => emit!(LedgerMintSkipped {
+            trading_account: trading_account_key,
+            mint,
+        }),
+    }
+
+    emit!(Deposited {
+        trading_account: ctx.accounts.trading_account.key(),
+        owner: ctx.accounts.user.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(id: u16)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
     /// The user's trading account PDA (for validation)
     #[account(
-        seeds = [b"trading_account", user.key().as_ref()],
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
         bump = trading_account.bump,
         constraint = trading_account.owner  == user.key() @ ErrorCode::Unauthorized
     )]
@@ -73,16 +93,11 @@ pub struct Deposit<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Amount must be greater than 0")]
-    InvalidAmount,
-    #[msg("Unauthorized: You don't own this trading account")]
-    Unauthorized,
-    #[msg("Arithmetic overflow")]
-    Overflow,
-    #[msg("Invalid token program: must be SPL Token or Token-2022")]
-    InvalidTokenProgram,
-    #[msg("Invalid token account owner: must be owned by the token program")]
-    InvalidTokenAccountOwner,
+#[event]
+pub struct Deposited {
+    pub trading_account: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    /// Amount deposited, in base units of `mint`
+    pub amount: u64,
 }
@@ -0,0 +1,99 @@
+use crate::errors::ErrorCode;
+use crate::instructions::deposit::Deposited;
+use crate::state::{LedgerMintSkipped, TradingAccount};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token};
+
+/// Deposit native SOL into the WSOL account of trading account `id`
+///
+/// Transfers `amount` lamports from the user to the trading account's WSOL
+/// ATA, creating the ATA first if it does not exist yet, and syncs it so the
+/// lamports show up as WSOL. Replaces the create ATA / transfer / sync_native
+/// sequence a client would otherwise have to send.
+pub fn deposit_sol(ctx: Context<DepositSol>, _id: u16, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    if ctx.accounts.pda_wsol_account.data_is_empty() {
+        anchor_spl::associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: ctx.accounts.user.to_account_info(),
+                associated_token: ctx.accounts.pda_wsol_account.to_account_info(),
+                authority: ctx.accounts.trading_account.to_account_info(),
+                mint: ctx.accounts.wsol_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.pda_wsol_account.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    token::sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        token::SyncNative {
+            account: ctx.accounts.pda_wsol_account.to_account_info(),
+        },
+    ))?;
+
+    let trading_account_key = ctx.accounts.trading_account.key();
+    match ctx.accounts.trading_account.ledger_mut(native_mint::ID) {
+        Some(entry) => entry.record_deposit(amount)?,
+        None => emit!(LedgerMintSkipped {
+            trading_account: trading_account_key,
+            mint: native_mint::ID,
+        }),
+    }
+
+    emit!(Deposited {
+        trading_account: ctx.accounts.trading_account.key(),
+        owner: ctx.accounts.user.key(),
+        mint: native_mint::ID,
+        amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct DepositSol<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The user's trading account PDA (for validation)
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+
+    /// CHECK: WSOL ATA of the trading account - created here if missing
+    #[account(
+        mut,
+        address = get_associated_token_address(&trading_account.key(), &native_mint::ID) @ ErrorCode::InvalidTokenAta
+    )]
+    pub pda_wsol_account: UncheckedAccount<'info>,
+
+    #[account(address = native_mint::ID @ ErrorCode::InvalidTokenMint)]
+    pub wsol_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,19 +1,29 @@
+use crate::pnl::PnlPosition;
 use crate::state::TradingAccount;
 use anchor_lang::prelude::*;
 
-/// Initialize a trading account (PDA) for a user
+/// Initialize trading account `id` (PDA) for a user
 ///
 /// In Ethereum terms: This is like deploying a smart contract wallet
-/// for the user. The PDA address is deterministic based on the user's pubkey.
-pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+/// for the user. The PDA address is deterministic based on the user's pubkey
+/// and the account index, so one wallet can keep several strategies apart.
+pub fn initialize(ctx: Context<Initialize>, id: u16) -> Result<()> {
     let trading_account = &mut ctx.accounts.trading_account;
 
     // Initialize the account state
     trading_account.owner = ctx.accounts.user.key();
     trading_account.bump = ctx.bumps.trading_account;
+    trading_account.version = TradingAccount::CURRENT_VERSION;
+    trading_account.id = id;
+    trading_account.pnl = PnlPosition::default();
+    trading_account.ledger_rebuild_pending = false;
+    trading_account.reserved = [0; 23];
+    trading_account.operators = Vec::new();
+    trading_account.ledger = Vec::new();
 
     msg!(
-        "Trading account initialized for user: {}",
+        "Trading account {} initialized for user: {}",
+        id,
         ctx.accounts.user.key()
     );
 
@@ -21,18 +31,19 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
 }
 
 #[derive(Accounts)]
+#[instruction(id: u16)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
     /// The PDA account that will store user's trading funds
-    /// Seeds: ["trading_account", user.key()]
+    /// Seeds: ["trading_account", user.key(), id (little endian, empty for 0)]
     /// This makes the address deterministic - same user always gets same PDA
     #[account(
         init,
         payer = user,
-        space = TradingAccount::LEN,
-        seeds = [b"trading_account", user.key().as_ref()],
+        space = TradingAccount::space(0),
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
         bump
     )]
     pub trading_account: Account<'info, TradingAccount>,
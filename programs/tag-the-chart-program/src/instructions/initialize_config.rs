@@ -0,0 +1,54 @@
+use crate::errors::ErrorCode;
+use crate::program::TagTheChartProgram;
+use crate::state::ProgramConfig;
+use anchor_lang::prelude::*;
+
+/// Initialize the program config PDA, unpaused and with an empty pool allowlist
+///
+/// Only the program's upgrade authority can create the config, which keeps
+/// anyone else from claiming the admin role first.
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    admin: Pubkey,
+    guardian: Pubkey,
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+
+    program_config.admin = admin;
+    program_config.guardian = guardian;
+    program_config.bump = ctx.bumps.program_config;
+    program_config.paused = false;
+    program_config.pools = Vec::new();
+
+    msg!(
+        "Program config initialized with admin: {}, guardian: {}",
+        admin,
+        guardian
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// Upgrade authority of the program
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ProgramConfig::LEN,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized)]
+    pub program: Program<'info, TagTheChartProgram>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,164 @@
+use crate::errors::ErrorCode;
+use crate::pnl::PnlPosition;
+use crate::state::TradingAccount;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Upgrade the user's legacy trading account to the current layout in place
+///
+/// Accounts created before the layout was versioned hold only `[owner, bump]`
+/// and are recognised by their size. Sub-accounts did not exist yet, so only
+/// account 0, at the original `["trading_account", owner]` address, can be
+/// one. It is grown to `TradingAccount::space(0)`, which includes room for a
+/// full ledger, and rewritten, keeping the same PDA address. The owner tops up
+/// the rent for the extra space. Every other instruction needs the current
+/// layout, so this must run first after an upgrade.
+///
+/// The legacy layout kept no ledger, so the migrated one starts empty and is
+/// marked for `rebuild_ledger`; the account cannot be closed before that.
+pub fn migrate_trading_account(ctx: Context<MigrateTradingAccount>) -> Result<()> {
+    let account_info = ctx.accounts.trading_account.to_account_info();
+
+    let (owner, bump) = {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            data.len() >= TradingAccount::LEGACY_LEN
+                && data.starts_with(TradingAccount::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        let owner = Pubkey::try_from_slice(&data[8..40])?;
+        require_keys_eq!(owner, ctx.accounts.user.key(), ErrorCode::Unauthorized);
+        require_legacy_layout(&data)?;
+        (owner, data[40])
+    };
+
+    let rent_due = Rent::get()?
+        .minimum_balance(TradingAccount::space(0))
+        .saturating_sub(account_info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    account_info.resize(TradingAccount::space(0))?;
+
+    let trading_account = TradingAccount {
+        owner,
+        bump,
+        version: TradingAccount::CURRENT_VERSION,
+        id: 0,
+        pnl: PnlPosition::default(),
+        ledger_rebuild_pending: true,
+        reserved: [0; 23],
+        operators: Vec::new(),
+        ledger: Vec::new(),
+    };
+    trading_account.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Trading account migrated to version {} for user: {}",
+        TradingAccount::CURRENT_VERSION,
+        owner
+    );
+
+    Ok(())
+}
+
+/// Layout version of serialized trading account `data`
+///
+/// The legacy layout has no version field, so it is recognised by its size
+/// and reported as version 0, which no versioned layout uses.
+fn stored_version(data: &[u8]) -> u8 {
+    if data.len() == TradingAccount::LEGACY_LEN {
+        0
+    } else {
+        data[TradingAccount::VERSION_OFFSET]
+    }
+}
+
+/// Fail unless `data` holds a layout this instruction can migrate
+fn require_legacy_layout(data: &[u8]) -> Result<()> {
+    match stored_version(data) {
+        0 => Ok(()),
+        TradingAccount::CURRENT_VERSION => err!(ErrorCode::AlreadyMigrated),
+        _ => err!(ErrorCode::UnsupportedVersion),
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigrateTradingAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Trading account PDA in an older layout, which cannot be
+    /// deserialized as `TradingAccount`, so the discriminator and owner are
+    /// checked by hand
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(0)],
+        bump,
+        owner = crate::ID
+    )]
+    pub trading_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::error::Error;
+
+    /// Discriminator, owner, bump and, past the legacy layout, `version`
+    fn account_data(len: usize, version: u8) -> Vec<u8> {
+        let mut data = vec![0; len];
+        data[..8].copy_from_slice(TradingAccount::DISCRIMINATOR);
+        if len > TradingAccount::VERSION_OFFSET {
+            data[TradingAccount::VERSION_OFFSET] = version;
+        }
+        data
+    }
+
+    fn error_code(result: Result<()>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            other => panic!("expected an Anchor error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn migrates_the_legacy_layout() {
+        let data = account_data(TradingAccount::LEGACY_LEN, 0);
+        assert_eq!(stored_version(&data), 0);
+        assert!(require_legacy_layout(&data).is_ok());
+    }
+
+    #[test]
+    fn refuses_the_current_layout() {
+        let data = account_data(TradingAccount::space(0), TradingAccount::CURRENT_VERSION);
+        assert_eq!(stored_version(&data), TradingAccount::CURRENT_VERSION);
+        assert_eq!(
+            error_code(require_legacy_layout(&data)),
+            u32::from(ErrorCode::AlreadyMigrated)
+        );
+    }
+
+    #[test]
+    fn refuses_unknown_versions() {
+        let data = account_data(
+            TradingAccount::space(0),
+            TradingAccount::CURRENT_VERSION + 1,
+        );
+        assert_eq!(
+            error_code(require_legacy_layout(&data)),
+            u32::from(ErrorCode::UnsupportedVersion)
+        );
+    }
+}
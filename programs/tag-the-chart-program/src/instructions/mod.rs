@@ -1,11 +1,29 @@
+pub mod close_trading_account;
+pub mod configure_pnl_pair;
 pub mod deposit;
+pub mod deposit_sol;
 pub mod initialize;
+pub mod initialize_config;
+pub mod migrate_trading_account;
+pub mod operators;
+pub mod pause;
+pub mod pool_allowlist;
+pub mod rebuild_ledger;
 pub mod swap;
 pub mod swap_simple;
 pub mod withdraw;
 
+pub use close_trading_account::*;
+pub use configure_pnl_pair::*;
 pub use deposit::*;
+pub use deposit_sol::*;
 pub use initialize::*;
+pub use initialize_config::*;
+pub use migrate_trading_account::*;
+pub use operators::*;
+pub use pause::*;
+pub use pool_allowlist::*;
+pub use rebuild_ledger::*;
 pub use swap::*;
 pub use swap_simple::*;
 pub use withdraw::*;
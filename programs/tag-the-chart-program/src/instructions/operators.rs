@@ -0,0 +1,93 @@
+use crate::errors::ErrorCode;
+use crate::state::{Operator, TradingAccount};
+use anchor_lang::prelude::*;
+
+/// Let `operator` call the swap instructions for this trading account
+///
+/// The account grows by one operator entry, paid by the owner. Operators can
+/// never withdraw; to change an operator's expiry, remove and add it again.
+pub fn add_operator(
+    ctx: Context<AddOperator>,
+    _id: u16,
+    operator: Pubkey,
+    expires_at: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at == 0 || expires_at > now,
+        ErrorCode::InvalidOperatorExpiry
+    );
+
+    let trading_account = &mut ctx.accounts.trading_account;
+    require!(
+        operator != trading_account.owner && trading_account.find_operator(&operator).is_none(),
+        ErrorCode::OperatorAlreadyAdded
+    );
+    require!(
+        trading_account.operators.len() < TradingAccount::MAX_OPERATORS,
+        ErrorCode::TooManyOperators
+    );
+
+    trading_account.operators.push(Operator {
+        key: operator,
+        expires_at,
+    });
+
+    msg!("Operator added: {} (expires at {})", operator, expires_at);
+
+    Ok(())
+}
+
+/// Revoke `operator`, shrinking the account and refunding the rent to the owner
+pub fn remove_operator(ctx: Context<RemoveOperator>, _id: u16, operator: Pubkey) -> Result<()> {
+    let trading_account = &mut ctx.accounts.trading_account;
+
+    let index = trading_account
+        .find_operator(&operator)
+        .ok_or(ErrorCode::OperatorNotFound)?;
+    trading_account.operators.swap_remove(index);
+
+    msg!("Operator removed: {}", operator);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct AddOperator<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized,
+        realloc = TradingAccount::space(trading_account.operators.len() + 1),
+        realloc::payer = user,
+        realloc::zero = false
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct RemoveOperator<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized,
+        realloc = TradingAccount::space(trading_account.operators.len().saturating_sub(1)),
+        realloc::payer = user,
+        realloc::zero = false
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+
+    pub system_program: Program<'info, System>,
+}
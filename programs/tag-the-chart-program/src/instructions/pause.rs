@@ -0,0 +1,55 @@
+use crate::errors::ErrorCode;
+use crate::state::ProgramConfig;
+use anchor_lang::prelude::*;
+
+/// Pause or resume every swap instruction
+///
+/// Deposits and withdrawals do not read the config, so users can always
+/// pull their funds out of a trading account while swaps are paused.
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.program_config.paused = paused;
+
+    msg!(
+        "Swaps {} by {}",
+        if paused { "paused" } else { "resumed" },
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
+
+/// Replace the guardian key
+pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+    ctx.accounts.program_config.guardian = guardian;
+
+    msg!("Guardian set: {}", guardian);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// Admin or guardian
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.can_pause(&authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
@@ -0,0 +1,73 @@
+use crate::errors::ErrorCode;
+use crate::state::ProgramConfig;
+use anchor_lang::prelude::*;
+use raydium_amm_v3::states::PoolState;
+
+/// Allow swaps on a Raydium CLMM pool
+///
+/// Taking the pool state account (rather than a bare key) makes sure only
+/// real Raydium pools end up on the list.
+pub fn add_pool(ctx: Context<AddPool>) -> Result<()> {
+    let pool = ctx.accounts.pool_state.key();
+    let program_config = &mut ctx.accounts.program_config;
+
+    require!(
+        !program_config.is_pool_allowed(&pool),
+        ErrorCode::PoolAlreadyAllowed
+    );
+    require!(
+        program_config.pools.len() < ProgramConfig::MAX_POOLS,
+        ErrorCode::PoolAllowlistFull
+    );
+
+    program_config.pools.push(pool);
+
+    msg!("Pool allowed: {}", pool);
+
+    Ok(())
+}
+
+/// Stop allowing swaps on a pool
+pub fn remove_pool(ctx: Context<RemovePool>, pool: Pubkey) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+
+    let index = program_config
+        .pools
+        .iter()
+        .position(|allowed| *allowed == pool)
+        .ok_or(ErrorCode::PoolNotAllowed)?;
+    program_config.pools.swap_remove(index);
+
+    msg!("Pool removed: {}", pool);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddPool<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Raydium CLMM pool to allow
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePool<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
@@ -0,0 +1,67 @@
+use crate::errors::ErrorCode;
+use crate::state::TradingAccount;
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::TokenAccount;
+
+/// Add the mint of every token account the trading account holds to its ledger
+///
+/// `remaining_accounts` lists the token accounts owned by trading account
+/// `id`. Mints the ledger does not track yet get an empty entry: what moved
+/// before the rebuild is unknown, so their totals start at zero. Closing only
+/// checks the token accounts of ledger mints, so pass every token account the
+/// trading account holds. Clears `ledger_rebuild_pending`, letting a migrated
+/// account be closed.
+pub fn rebuild_ledger(ctx: Context<RebuildLedger>, _id: u16) -> Result<()> {
+    let trading_account_key = ctx.accounts.trading_account.key();
+
+    for (index, account) in ctx.remaining_accounts.iter().enumerate() {
+        require!(
+            !ctx.remaining_accounts[..index]
+                .iter()
+                .any(|other| other.key == account.key),
+            ErrorCode::DuplicateTokenAccount
+        );
+        require!(
+            *account.owner == token::ID || *account.owner == token_2022::ID,
+            ErrorCode::InvalidTokenProgram
+        );
+
+        let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        require_keys_eq!(
+            token_account.owner,
+            trading_account_key,
+            ErrorCode::InvalidTokenAccountOwner
+        );
+
+        ctx.accounts
+            .trading_account
+            .ledger_mut(token_account.mint)
+            .ok_or(error!(ErrorCode::TooManyLedgerMints))?;
+    }
+
+    let trading_account = &mut ctx.accounts.trading_account;
+    trading_account.ledger_rebuild_pending = false;
+
+    msg!(
+        "Ledger rebuilt: {} mints tracked",
+        trading_account.ledger.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16)]
+pub struct RebuildLedger<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
+        bump = trading_account.bump,
+        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub trading_account: Account<'info, TradingAccount>,
+}
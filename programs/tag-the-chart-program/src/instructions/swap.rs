@@ -1,4 +1,9 @@
-use crate::state::TradingAccount;
+use crate::candle::{CandleExecuted, CandleTracker};
+use crate::errors::ErrorCode;
+use crate::path::{build_path, build_tick_path, OpenPriceGuard, PathStep, SpendLimits};
+use crate::price::{DecimalPrices, PriceOffsets};
+use crate::state::{LedgerMintSkipped, ProgramConfig, TradingAccount};
+use crate::utils::token_balance;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
@@ -10,118 +15,323 @@ use raydium_amm_v3::{
 
 declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
+#[allow(clippy::too_many_arguments)]
 pub fn swap_to_prices<'info>(
     ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
-    to_sqrt_prices: [u128; 3], // Target sqrt prices (X64 format) for each swap
-    max_inputs: [u64; 3],      // Max input amounts (0 = no limit)
-    min_outputs: [u64; 3],     // Min output amounts (0 = no limit)
+    id: u16,
+    to_sqrt_prices: Vec<u128>, // Target sqrt prices (X64 format) for each swap
+    max_inputs: Vec<u64>,      // Max input amounts (0 = no limit)
+    min_outputs: Vec<u64>,     // Min output amounts (0 = no limit)
+    tolerances_bps: Vec<u16>,  // Price-reached tolerance per step (empty = default)
+    exact_outputs: Vec<bool>,  // Exact-output mode per step (empty = all exact input)
+    open_guard: Option<OpenPriceGuard>, // Abort if the pool moved too far before the first step
+    spend_limits: Option<SpendLimits>, // Abort if the whole path cost more than allowed
+) -> Result<()> {
+    let path = build_path(
+        &to_sqrt_prices,
+        &max_inputs,
+        &min_outputs,
+        &tolerances_bps,
+        &exact_outputs,
+    )?;
+
+    execute_path(ctx, id, &path, open_guard, spend_limits)
+}
+
+/// Same as `swap_to_prices`, with targets given as offsets from the open price
+#[allow(clippy::too_many_arguments)]
+pub fn swap_to_offsets<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    id: u16,
+    target_offsets: PriceOffsets,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    tolerances_bps: Vec<u16>,
+    exact_outputs: Vec<bool>,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let open_sqrt_price = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.sqrt_price_x64
+    };
+    let to_sqrt_prices = target_offsets.to_sqrt_prices(open_sqrt_price)?;
+
+    swap_to_prices(
+        ctx,
+        id,
+        to_sqrt_prices,
+        max_inputs,
+        min_outputs,
+        tolerances_bps,
+        exact_outputs,
+        open_guard,
+        spend_limits,
+    )
+}
+
+/// Same as `swap_to_prices`, with targets given as tick indexes
+///
+/// Each step must land exactly on its tick, so there is no price tolerance.
+pub fn swap_to_ticks<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    id: u16,
+    target_ticks: Vec<i32>,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    exact_outputs: Vec<bool>,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let tick_spacing = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.tick_spacing
+    };
+    let path = build_tick_path(
+        &target_ticks,
+        tick_spacing,
+        &max_inputs,
+        &min_outputs,
+        &exact_outputs,
+    )?;
+
+    execute_path(ctx, id, &path, open_guard, spend_limits)
+}
+
+/// Swap through every step of `path` and emit the candle summary
+fn execute_path<'info>(
+    mut ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    id: u16,
+    path: &[PathStep],
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
 ) -> Result<()> {
     let user_key = ctx.accounts.user.key();
+    let owner = ctx.accounts.trading_account.owner;
     let bump = ctx.accounts.trading_account.bump;
+    let id_seed = TradingAccount::id_seed(id);
 
-    let seeds = &[b"trading_account", user_key.as_ref(), &[bump]];
+    let seeds = &[b"trading_account", owner.as_ref(), &id_seed, &[bump]];
     let signer = &[&seeds[..]];
 
-    for (i, to_sqrt_price) in to_sqrt_prices.iter().enumerate() {
+    let open_sqrt_price = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.sqrt_price_x64
+    };
+    if let Some(open_guard) = open_guard {
+        open_guard.check(open_sqrt_price)?;
+    }
+    let mut candle = CandleTracker::new(
+        open_sqrt_price,
+        ctx.accounts.amm_config.trade_fee_rate,
+        path.len(),
+    );
+    let balances_open = token_balances(&ctx)?;
+    let mut pnl_updated = false;
+
+    for step in path.iter() {
         let from_sqrt_price = {
             let pool = ctx.accounts.pool_state.load()?;
             pool.sqrt_price_x64
         };
 
-        let price_increasing = *to_sqrt_price > from_sqrt_price;
+        let price_increasing = step.target_sqrt_price > from_sqrt_price;
         let a_to_b = !price_increasing;
 
-        swap_to_price(
-            &ctx,
-            signer,
-            *to_sqrt_price,
-            a_to_b,
-            max_inputs[i],
-            min_outputs[i],
-        )?;
+        let balances_before = token_balances(&ctx)?;
+        swap_to_price(&ctx, signer, step, a_to_b)?;
+        let balances_after = token_balances(&ctx)?;
+        candle.record_swap(a_to_b, balances_before, balances_after, 0)?;
+        pnl_updated |= record_pnl_fill(&mut ctx, a_to_b, balances_before, balances_after)?;
+
+        let actual_sqrt_price = verify_price_reached(&ctx, from_sqrt_price, step)?;
+        candle.record_price(actual_sqrt_price);
+    }
+
+    if let Some(spend_limits) = spend_limits {
+        spend_limits.check(balances_open, token_balances(&ctx)?, (0, 0))?;
+    }
+
+    let candle = candle.finish(ctx.accounts.pool_state.key(), user_key);
+    record_ledger(&mut ctx, &candle)?;
+    emit!(candle);
+
+    if pnl_updated {
+        let pnl = ctx.accounts.trading_account.pnl;
+        emit!(PositionUpdated {
+            trading_account: ctx.accounts.trading_account.key(),
+            base_mint: pnl.base_mint,
+            quote_mint: pnl.quote_mint,
+            position: pnl.position,
+            cost_basis: pnl.cost_basis,
+            average_entry_price_x64: pnl.average_entry_price_x64(),
+            realized_pnl: pnl.realized_pnl,
+        });
+    }
+
+    Ok(())
+}
 
-        verify_price_reached(&ctx, from_sqrt_price, *to_sqrt_price)?;
+/// Same as `swap_to_prices`, with targets given as decimal prices
+///
+/// Prices are converted on-chain with the decimals of `token_mint_a` and
+/// `token_mint_b`.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_to_decimal_prices<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    id: u16,
+    target_prices: DecimalPrices,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    tolerances_bps: Vec<u16>,
+    exact_outputs: Vec<bool>,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let to_sqrt_prices = target_prices.to_sqrt_prices(
+        ctx.accounts.token_mint_a.decimals,
+        ctx.accounts.token_mint_b.decimals,
+    )?;
+
+    swap_to_prices(
+        ctx,
+        id,
+        to_sqrt_prices,
+        max_inputs,
+        min_outputs,
+        tolerances_bps,
+        exact_outputs,
+        open_guard,
+        spend_limits,
+    )
+}
+
+/// Add what a candle execution moved to the trading account's ledger
+fn record_ledger<'info>(
+    ctx: &mut Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    candle: &CandleExecuted,
+) -> Result<()> {
+    let trading_account_key = ctx.accounts.trading_account.key();
+    let legs = [
+        (
+            ctx.accounts.token_mint_a.key(),
+            candle.total_in_a,
+            candle.total_out_a,
+            candle.estimated_fee_a,
+        ),
+        (
+            ctx.accounts.token_mint_b.key(),
+            candle.total_in_b,
+            candle.total_out_b,
+            candle.estimated_fee_b,
+        ),
+    ];
+
+    for (mint, spent, received, estimated_fees) in legs {
+        match ctx.accounts.trading_account.ledger_mut(mint) {
+            Some(entry) => entry.record_swap(spent, received, estimated_fees)?,
+            None => emit!(LedgerMintSkipped {
+                trading_account: trading_account_key,
+                mint,
+            }),
+        }
     }
 
     Ok(())
 }
 
-/// Verify that the swap reached the target price within tolerance and emit event
+/// Update the trading account's cost basis with one swap leg, from the
+/// `(a, b)` balances around it; returns whether the leg traded the PnL pair
+fn record_pnl_fill<'info>(
+    ctx: &mut Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+    a_to_b: bool,
+    before: (u64, u64),
+    after: (u64, u64),
+) -> Result<bool> {
+    let mint_a = ctx.accounts.token_mint_a.key();
+    let mint_b = ctx.accounts.token_mint_b.key();
+    let (mint_in, amount_in, mint_out, amount_out) = if a_to_b {
+        (
+            mint_a,
+            before.0.saturating_sub(after.0),
+            mint_b,
+            after.1.saturating_sub(before.1),
+        )
+    } else {
+        (
+            mint_b,
+            before.1.saturating_sub(after.1),
+            mint_a,
+            after.0.saturating_sub(before.0),
+        )
+    };
+
+    ctx.accounts
+        .trading_account
+        .pnl
+        .record_fill(mint_in, amount_in, mint_out, amount_out)
+}
+
+/// Current `(a, b)` balances of the trading account's token accounts
+fn token_balances<'info>(
+    ctx: &Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+) -> Result<(u64, u64)> {
+    Ok((
+        token_balance(&ctx.accounts.trading_account_token_a.to_account_info())?,
+        token_balance(&ctx.accounts.trading_account_token_b.to_account_info())?,
+    ))
+}
+
+/// Verify that the swap reached the target price within tolerance and emit event,
+/// returning the price reached
 fn verify_price_reached<'info>(
     ctx: &Context<'_, '_, '_, 'info, SwapCandle<'info>>,
     from_sqrt_price: u128,
-    to_sqrt_price: u128,
-) -> Result<()> {
-    let actual_sqrt_price = {
+    step: &PathStep,
+) -> Result<u128> {
+    let (actual_sqrt_price, tick_current) = {
         let pool = ctx.accounts.pool_state.load()?;
-        pool.sqrt_price_x64
+        (pool.sqrt_price_x64, pool.tick_current)
     };
 
-    let diff = if actual_sqrt_price > to_sqrt_price {
-        actual_sqrt_price - to_sqrt_price
-    } else {
-        to_sqrt_price - actual_sqrt_price
-    };
-
-    let tolerance = to_sqrt_price / 1000;
-    require!(diff <= tolerance, ErrorCode::PriceNotReached);
+    require!(
+        step.is_reached(actual_sqrt_price, tick_current),
+        ErrorCode::PriceNotReached
+    );
 
     emit!(SwapExecuted {
         from_sqrt_price,
-        to_sqrt_price,
+        to_sqrt_price: step.target_sqrt_price,
         actual_sqrt_price,
     });
 
-    Ok(())
+    Ok(actual_sqrt_price)
 }
 
 /// Executes a swap to a target price with optional input/output limits.
 /// Uses sqrt_price_limit to stop at the exact target price.
+/// In exact-output mode the target only bounds the price and the step must
+/// receive exactly `min_output`.
 fn swap_to_price<'info>(
     ctx: &Context<'_, '_, '_, 'info, SwapCandle<'info>>,
     signer_seeds: &[&[&[u8]]],
-    to_sqrt_price: u128,
+    step: &PathStep,
     a_to_b: bool,
-    max_input: u64,
-    min_output: u64,
 ) -> Result<()> {
-    let amount_specified = if max_input == 0 { u64::MAX } else { max_input };
-    let minimum_amount_out = min_output;
-
-    let wsol_mint = pubkey!("So11111111111111111111111111111111111111112");
-    let is_mint_a_wsol = ctx.accounts.token_mint_a.key() == wsol_mint;
-
-    // Map trading account token accounts to A/B positions based on a_to_b direction
-    let (input_token_account, output_token_account) = if a_to_b {
-        // Swapping from A to B
-        if is_mint_a_wsol {
-            (
-                ctx.accounts.trading_account_wsol.to_account_info(),
-                ctx.accounts.trading_account_token.to_account_info(),
-            )
-        } else {
-            (
-                ctx.accounts.trading_account_token.to_account_info(),
-                ctx.accounts.trading_account_wsol.to_account_info(),
-            )
-        }
-    } else {
-        // Swapping from B to A
-        if is_mint_a_wsol {
-            (
-                ctx.accounts.trading_account_token.to_account_info(),
-                ctx.accounts.trading_account_wsol.to_account_info(),
-            )
-        } else {
-            (
-                ctx.accounts.trading_account_wsol.to_account_info(),
-                ctx.accounts.trading_account_token.to_account_info(),
-            )
-        }
-    };
+    let (amount, other_amount_threshold) = step.swap_amounts();
 
-    let (input_vault, output_vault, input_vault_mint, output_vault_mint) = if a_to_b {
+    // Map trading account token accounts and pool vaults to input/output by direction
+    let (
+        input_token_account,
+        output_token_account,
+        input_vault,
+        output_vault,
+        input_vault_mint,
+        output_vault_mint,
+    ) = if a_to_b {
         (
+            ctx.accounts.trading_account_token_a.to_account_info(),
+            ctx.accounts.trading_account_token_b.to_account_info(),
             ctx.accounts.token_vault_a.to_account_info(),
             ctx.accounts.token_vault_b.to_account_info(),
             ctx.accounts.token_mint_a.to_account_info(),
@@ -129,6 +339,8 @@ fn swap_to_price<'info>(
         )
     } else {
         (
+            ctx.accounts.trading_account_token_b.to_account_info(),
+            ctx.accounts.trading_account_token_a.to_account_info(),
             ctx.accounts.token_vault_b.to_account_info(),
             ctx.accounts.token_vault_a.to_account_info(),
             ctx.accounts.token_mint_b.to_account_info(),
@@ -153,6 +365,9 @@ fn swap_to_price<'info>(
         memo_program: ctx.accounts.memo_program.to_account_info(),
     };
 
+    let output_before = token_balance(&cpi_accounts.output_token_account)?;
+    let output_token_account = cpi_accounts.output_token_account.clone();
+
     let raydium = ctx.accounts.raydium_program.to_account_info();
     let cpi_ctx = CpiContext::new_with_signer(raydium, cpi_accounts, signer_seeds)
         .with_remaining_accounts(ctx.remaining_accounts.to_vec());
@@ -160,64 +375,87 @@ fn swap_to_price<'info>(
     // Execute swap_v2 with target sqrt price as limit
     cpi::swap_v2(
         cpi_ctx,
-        amount_specified,
-        minimum_amount_out,
-        to_sqrt_price,
-        true,
+        amount,
+        other_amount_threshold,
+        step.target_sqrt_price,
+        !step.exact_output, // is_base_input
     )?;
 
+    if step.exact_output {
+        let received = token_balance(&output_token_account)?.saturating_sub(output_before);
+        require!(received >= amount, ErrorCode::ExactOutputNotFilled);
+    }
+
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(target_sqrt_prices: [u128; 3], max_inputs: [u64; 3], min_outputs: [u64; 3])]
+#[instruction(id: u16)]
 pub struct SwapCandle<'info> {
+    /// Owner of the trading account or one of its active operators
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Trading account `id` of its owner, who may differ from `user` when an
+    /// operator signs
     #[account(
         mut,
-        seeds = [b"trading_account", user.key().as_ref()],
+        seeds = [
+            b"trading_account",
+            trading_account.owner.as_ref(),
+            &TradingAccount::id_seed(id)
+        ],
         bump = trading_account.bump,
-        constraint = trading_account.owner == user.key() @ ErrorCode::Unauthorized
+        constraint = trading_account.can_trade(&user.key(), Clock::get()?.unix_timestamp) @ ErrorCode::Unauthorized
     )]
     pub trading_account: Account<'info, TradingAccount>,
 
     pub raydium_program: Program<'info, AmmV3>,
 
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused,
+        constraint = program_config.is_pool_allowed(&pool_state.key()) @ ErrorCode::PoolNotAllowed
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
+    #[account(address = pool_state.load()?.amm_config @ ErrorCode::InvalidAmmConfig)]
     pub amm_config: Box<Account<'info, AmmConfig>>,
 
     #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// Token2022 account for the non-SOL token (owned by trading_account PDA)
+    /// Token account for token mint A (owned by trading_account PDA)
     #[account(
         mut,
-        constraint = trading_account_token.owner == trading_account.key()
+        token::authority = trading_account,
+        constraint = trading_account_token_a.mint == token_mint_a.key() @ ErrorCode::InvalidTokenMint
     )]
-    pub trading_account_token: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub trading_account_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Wrapped SOL account (owned by trading_account PDA)
+    /// Token account for token mint B (owned by trading_account PDA)
     #[account(
         mut,
-        constraint = trading_account_wsol.owner == trading_account.key()
+        token::authority = trading_account,
+        constraint = trading_account_token_b.mint == token_mint_b.key() @ ErrorCode::InvalidTokenMint
     )]
-    pub trading_account_wsol: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub trading_account_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut)]
+    #[account(mut, address = pool_state.load()?.token_vault_0 @ ErrorCode::InvalidTokenVault)]
     pub token_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut)]
+    #[account(mut, address = pool_state.load()?.token_vault_1 @ ErrorCode::InvalidTokenVault)]
     pub token_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(address = token_vault_a.mint)]
+    #[account(address = pool_state.load()?.token_mint_0 @ ErrorCode::InvalidPoolMint)]
     pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(address = token_vault_b.mint)]
+    #[account(address = pool_state.load()?.token_mint_1 @ ErrorCode::InvalidPoolMint)]
     pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: Observation state is validated by Raydium CLMM
-    #[account(mut)]
+    /// CHECK: Observation state must be the pool's observation account
+    #[account(mut, address = pool_state.load()?.observation_key @ ErrorCode::InvalidObservationState)]
     pub observation_state: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -231,17 +469,24 @@ pub struct SwapCandle<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Unauthorized: You don't own this trading account")]
-    Unauthorized,
-    #[msg("Price not reached: swap did not reach target price within tolerance")]
-    PriceNotReached,
-}
-
 #[event]
 pub struct SwapExecuted {
     pub from_sqrt_price: u128,
     pub to_sqrt_price: u128,
     pub actual_sqrt_price: u128,
 }
+
+#[event]
+pub struct PositionUpdated {
+    pub trading_account: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    /// Base bought by swaps and not sold yet
+    pub position: u64,
+    /// Quote paid for `position`, fees included
+    pub cost_basis: u128,
+    /// `cost_basis / position` as a Q64.64 number
+    pub average_entry_price_x64: u128,
+    /// Running realized PnL in quote units
+    pub realized_pnl: i128,
+}
@@ -1,10 +1,16 @@
+use crate::candle::CandleTracker;
+use crate::errors::ErrorCode;
+use crate::path::{build_path, build_tick_path, OpenPriceGuard, PathStep, SpendLimits};
+use crate::price::{DecimalPrices, PriceOffsets};
+use crate::quote::quote_to_price;
+use crate::state::ProgramConfig;
+use crate::utils::token_balance;
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 use raydium_amm_v3::{
     cpi,
-    libraries::swap_math,
     program::AmmV3,
     states::{AmmConfig, PoolState},
 };
@@ -13,46 +19,241 @@ declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
 const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
-/// Execute 3 atomic swaps to create OHLC candle pattern
+/// Execute one atomic swap per path step to draw a candle pattern
 /// Simplified version without PDA - uses wallet's ATAs directly
+///
+/// Works with any pool pair. Native SOL is wrapped on the fly only when
+/// WSOL is the input side of a step. With `unwrap_sol` the wallet's WSOL ATA
+/// is closed at the end, returning its whole balance as native SOL, including
+/// WSOL it held before the path. With `open_guard` nothing is swapped unless
+/// the pool still opens where the path was planned from, and with
+/// `spend_limits` the whole path reverts if it cost more than allowed.
+#[allow(clippy::too_many_arguments)]
 pub fn swap_to_prices_simple<'info>(
-    ctx: Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
-    to_sqrt_prices: [u128; 3], // Target sqrt prices (X64 format) for each swap
-    max_inputs: [u64; 3],      // Max input amounts (0 = no limit)
-    min_outputs: [u64; 3],     // Min output amounts (0 = no limit)
+    ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    to_sqrt_prices: Vec<u128>, // Target sqrt prices (X64 format) for each swap
+    max_inputs: Vec<u64>,      // Max input amounts (0 = no limit)
+    min_outputs: Vec<u64>,     // Min output amounts (0 = no limit)
+    tolerances_bps: Vec<u16>,  // Price-reached tolerance per step (empty = default)
+    exact_outputs: Vec<bool>,  // Exact-output mode per step (empty = all exact input)
+    unwrap_sol: bool,          // Close the WSOL ATA back to native SOL at the end
+    open_guard: Option<OpenPriceGuard>, // Abort if the pool moved too far before the first step
+    spend_limits: Option<SpendLimits>, // Abort if the whole path cost more than allowed
 ) -> Result<()> {
-    require_valid_wsol_ata(&ctx)?;
+    let path = build_path(
+        &to_sqrt_prices,
+        &max_inputs,
+        &min_outputs,
+        &tolerances_bps,
+        &exact_outputs,
+    )?;
+
+    execute_path(ctx, &path, unwrap_sol, open_guard, spend_limits)
+}
+
+/// Same as `swap_to_prices_simple`, with targets given as offsets from the open price
+#[allow(clippy::too_many_arguments)]
+pub fn swap_to_offsets_simple<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    target_offsets: PriceOffsets,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    tolerances_bps: Vec<u16>,
+    exact_outputs: Vec<bool>,
+    unwrap_sol: bool,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let open_sqrt_price = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.sqrt_price_x64
+    };
+    let to_sqrt_prices = target_offsets.to_sqrt_prices(open_sqrt_price)?;
+
+    swap_to_prices_simple(
+        ctx,
+        to_sqrt_prices,
+        max_inputs,
+        min_outputs,
+        tolerances_bps,
+        exact_outputs,
+        unwrap_sol,
+        open_guard,
+        spend_limits,
+    )
+}
+
+/// Same as `swap_to_prices_simple`, with targets given as tick indexes
+///
+/// Each step must land exactly on its tick, so there is no price tolerance.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_to_ticks_simple<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    target_ticks: Vec<i32>,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    exact_outputs: Vec<bool>,
+    unwrap_sol: bool,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let tick_spacing = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.tick_spacing
+    };
+    let path = build_tick_path(
+        &target_ticks,
+        tick_spacing,
+        &max_inputs,
+        &min_outputs,
+        &exact_outputs,
+    )?;
+
+    execute_path(ctx, &path, unwrap_sol, open_guard, spend_limits)
+}
+
+/// Swap through every step of `path`, then settle WSOL and emit the candle summary
+fn execute_path<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    path: &[PathStep],
+    unwrap_sol: bool,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let open_sqrt_price = {
+        let pool = ctx.accounts.pool_state.load()?;
+        pool.sqrt_price_x64
+    };
+    if let Some(open_guard) = open_guard {
+        open_guard.check(open_sqrt_price)?;
+    }
+
+    require_token_accounts(&ctx)?;
+
+    let wsol_account = wsol_token_account(&ctx);
+    let wsol_before = match &wsol_account {
+        Some(account) => token_balance(account)?,
+        None => 0,
+    };
+    let mut wrapped = 0u64;
+    let balances_open = token_balances(&ctx)?;
+
+    let mut candle = CandleTracker::new(
+        open_sqrt_price,
+        ctx.accounts.amm_config.trade_fee_rate,
+        path.len(),
+    );
 
     // Execute swaps
-    for (i, to_sqrt_price) in to_sqrt_prices.iter().enumerate() {
+    for step in path.iter() {
         let from_sqrt_price = {
             let pool = ctx.accounts.pool_state.load()?;
             pool.sqrt_price_x64
         };
 
-        let a_for_b = !(*to_sqrt_price > from_sqrt_price);
-        swap_to_price(&ctx, *to_sqrt_price, a_for_b, max_inputs[i], min_outputs[i])?;
+        let a_for_b = step.target_sqrt_price <= from_sqrt_price;
+        let balances_before = token_balances(&ctx)?;
+        let step_wrapped = swap_to_price(&ctx, step, a_for_b)?;
+        wrapped = wrapped
+            .checked_add(step_wrapped)
+            .ok_or(ErrorCode::Overflow)?;
+        candle.record_swap(
+            a_for_b,
+            balances_before,
+            token_balances(&ctx)?,
+            step_wrapped,
+        )?;
+
+        let actual_sqrt_price = require_price_reached(&ctx, from_sqrt_price, step)?;
+        candle.record_price(actual_sqrt_price);
+    }
+
+    if let Some(spend_limits) = spend_limits {
+        // Only one side of the pool can be WSOL, so all wrapped SOL went there
+        let added = if ctx.accounts.token_mint_a.key() == WSOL_MINT {
+            (wrapped, 0)
+        } else {
+            (0, wrapped)
+        };
+        spend_limits.check(balances_open, token_balances(&ctx)?, added)?;
+    }
 
-        require_price_reached(&ctx, from_sqrt_price, *to_sqrt_price)?;
+    if let Some(wsol_account) = wsol_account {
+        settle_wsol(&ctx, wsol_account, wsol_before, wrapped, unwrap_sol)?;
     }
 
+    emit!(candle.finish(ctx.accounts.pool_state.key(), ctx.accounts.wallet.key()));
+
     Ok(())
 }
 
-/// Execute a single swap to target price
+/// Same as `swap_to_prices_simple`, with targets given as decimal prices
+///
+/// Prices are converted on-chain with the decimals of `token_mint_a` and
+/// `token_mint_b`.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_to_decimal_prices_simple<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    target_prices: DecimalPrices,
+    max_inputs: Vec<u64>,
+    min_outputs: Vec<u64>,
+    tolerances_bps: Vec<u16>,
+    exact_outputs: Vec<bool>,
+    unwrap_sol: bool,
+    open_guard: Option<OpenPriceGuard>,
+    spend_limits: Option<SpendLimits>,
+) -> Result<()> {
+    let to_sqrt_prices = target_prices.to_sqrt_prices(
+        ctx.accounts.token_mint_a.decimals,
+        ctx.accounts.token_mint_b.decimals,
+    )?;
+
+    swap_to_prices_simple(
+        ctx,
+        to_sqrt_prices,
+        max_inputs,
+        min_outputs,
+        tolerances_bps,
+        exact_outputs,
+        unwrap_sol,
+        open_guard,
+        spend_limits,
+    )
+}
+
+/// Current `(a, b)` balances of the wallet's token accounts
+fn token_balances<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+) -> Result<(u64, u64)> {
+    Ok((
+        token_balance(&ctx.accounts.token_account_a)?,
+        token_balance(&ctx.accounts.token_account_b)?,
+    ))
+}
+
+/// Execute a single swap to target price, returning the lamports wrapped for it
 fn swap_to_price<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
-    to_sqrt_price: u128,
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    step: &PathStep,
     a_for_b: bool,
-    max_in: u64,
-    min_out: u64,
-) -> Result<()> {
-    let (amount_in, amount_out) = get_amount_in(ctx, to_sqrt_price, a_for_b, max_in, min_out)?;
+) -> Result<u64> {
+    let to_sqrt_price = step.target_sqrt_price;
+    let (amount_in, amount_out) = get_amount_in(ctx, step, a_for_b)?;
+
+    let wrapped = wrap_sol(ctx, amount_in, a_for_b)?;
 
-    wrap_sol(ctx, amount_in, a_for_b)?;
+    // Exact input spends the quoted input, exact output buys the requested output
+    let (amount, other_amount_threshold) = if step.exact_output {
+        (amount_out, step.swap_amounts().1)
+    } else {
+        (amount_in, amount_out)
+    };
 
     // Build CPI accounts for Raydium swap
     let cpi_accounts = build_swap_cpi_accounts(ctx, a_for_b);
+    let output_token_account = cpi_accounts.output_token_account.clone();
+    let output_before = token_balance(&output_token_account)?;
+
     let program = ctx.accounts.raydium_program.to_account_info();
     let cpi_ctx = CpiContext::new(program, cpi_accounts)
         .with_remaining_accounts(ctx.remaining_accounts.to_vec());
@@ -60,103 +261,93 @@ fn swap_to_price<'info>(
     // Execute swap_v2 with target sqrt price as limit
     cpi::swap_v2(
         cpi_ctx,
-        amount_in,
-        amount_out,
+        amount,
+        other_amount_threshold,
         to_sqrt_price,
-        true, // is_base_input
+        !step.exact_output, // is_base_input
     )?;
 
+    if step.exact_output {
+        let received = token_balance(&output_token_account)?.saturating_sub(output_before);
+        require!(received >= amount_out, ErrorCode::ExactOutputNotFilled);
+    }
+
     emit!(Swap {
         a_for_b,
         amount_in,
         amount_out,
     });
 
-    Ok(())
+    Ok(wrapped)
 }
 
 /// Calculate exact input amount needed to reach target price
+///
+/// Walks every initialized tick between the current and the target price using
+/// the tick arrays passed in `remaining_accounts`, so the amount matches what
+/// Raydium will actually swap. The returned input includes trading fees.
+///
+/// For exact-input steps the output is the minimum accepted output; for
+/// exact-output steps it is the requested output and the input is what
+/// buying it will cost.
 fn get_amount_in<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
-    to_sqrt_price: u128,
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    step: &PathStep,
     a_for_b: bool,
-    max_in: u64,
-    min_out: u64,
 ) -> Result<(u64, u64)> {
-    // Get current pool state
-
-    let amount_remaining = if max_in == 0 { u64::MAX } else { max_in };
-
-    let pool = ctx.accounts.pool_state.load()?;
-    let current_sqrt_price = pool.sqrt_price_x64;
-    let liquidity = pool.liquidity;
-    drop(pool);
-
-    // Get amm config for fee rate
-    let fee_rate = ctx.accounts.amm_config.trade_fee_rate;
-
-    // Get current timestamp
-    let clock = Clock::get()?;
-    let block_timestamp = clock.unix_timestamp as u32;
+    let amount = if step.exact_output {
+        step.min_output
+    } else {
+        step.swap_amounts().0
+    };
 
-    // Use Raydium's compute_swap_step to calculate exact amount
-    let swap_step = swap_math::compute_swap_step(
-        current_sqrt_price,
-        to_sqrt_price,
-        liquidity,
-        amount_remaining,
-        fee_rate,
-        true,
+    let quote = quote_to_price(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.amm_config,
+        ctx.remaining_accounts,
+        step.target_sqrt_price,
         a_for_b,
-        block_timestamp,
+        amount,
+        !step.exact_output,
     )?;
 
-    Ok((swap_step.amount_in, swap_step.amount_out.min(min_out)))
+    if step.exact_output {
+        Ok((quote.amount_in, step.min_output))
+    } else {
+        Ok((quote.amount_in, quote.amount_out.min(step.min_output)))
+    }
 }
 
-/// Wrap SOL to WSOL if this swap requires it
+/// Wrap SOL to WSOL if the input side of this swap is WSOL, returning the lamports wrapped
 fn wrap_sol<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
     amount: u64,
     a_for_b: bool,
-) -> Result<()> {
-    let is_mint_a_wsol = ctx.accounts.token_mint_a.key() == WSOL_MINT;
-    let need_to_wrap = (a_for_b && is_mint_a_wsol) || (!a_for_b && !is_mint_a_wsol);
-
-    if !need_to_wrap {
-        return Ok(());
-    }
-
-    // Get the WSOL mint account (either mint_a or mint_b)
-    let wsol_mint_account = if is_mint_a_wsol {
-        ctx.accounts.token_mint_a.to_account_info()
+) -> Result<u64> {
+    let (input_mint, input_token_account) = if a_for_b {
+        (
+            ctx.accounts.token_mint_a.key(),
+            ctx.accounts.token_account_a.to_account_info(),
+        )
     } else {
-        ctx.accounts.token_mint_b.to_account_info()
+        (
+            ctx.accounts.token_mint_b.key(),
+            ctx.accounts.token_account_b.to_account_info(),
+        )
     };
 
-    let wsol_ata_info = ctx.accounts.wsol_ata.to_account_info();
-    if wsol_ata_info.data_is_empty() {
-        anchor_spl::associated_token::create(CpiContext::new(
-            ctx.accounts.associated_token_program.to_account_info(),
-            anchor_spl::associated_token::Create {
-                payer: ctx.accounts.wallet.to_account_info(),
-                associated_token: wsol_ata_info.clone(),
-                authority: ctx.accounts.wallet.to_account_info(),
-                mint: wsol_mint_account.clone(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-            },
-        ))?;
+    if input_mint != WSOL_MINT {
+        return Ok(0);
     }
 
-    let wrap_amount = amount + 1000; // +1000 lamports buffer
+    let wrap_amount = amount.checked_add(1000).ok_or(ErrorCode::Overflow)?; // +1000 lamports buffer
 
     anchor_lang::system_program::transfer(
         CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
             anchor_lang::system_program::Transfer {
                 from: ctx.accounts.wallet.to_account_info(),
-                to: wsol_ata_info.clone(),
+                to: input_token_account.clone(),
             },
         ),
         wrap_amount,
@@ -165,49 +356,86 @@ fn wrap_sol<'info>(
     anchor_spl::token::sync_native(CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         anchor_spl::token::SyncNative {
-            account: wsol_ata_info,
+            account: input_token_account,
         },
     ))?;
 
+    Ok(wrap_amount)
+}
+
+/// The wallet's WSOL ATA, if either side of the pool is WSOL
+fn wsol_token_account<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+) -> Option<AccountInfo<'info>> {
+    if ctx.accounts.token_mint_a.key() == WSOL_MINT {
+        Some(ctx.accounts.token_account_a.to_account_info())
+    } else if ctx.accounts.token_mint_b.key() == WSOL_MINT {
+        Some(ctx.accounts.token_account_b.to_account_info())
+    } else {
+        None
+    }
+}
+
+/// Report the SOL wrapped for the path and optionally unwrap what is left
+///
+/// `spent` is the net WSOL that left the ATA over the whole path, so WSOL
+/// bought back on sell steps is netted out. Unwrapping closes the ATA, which
+/// SPL Token only allows for the whole account: WSOL the wallet held before
+/// the path is unwrapped too and counted in `returned`, with `held_before`
+/// reported so it can be told apart. The ATA's rent also comes back to the
+/// wallet but is not counted in `returned`.
+fn settle_wsol<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+    wsol_account: AccountInfo<'info>,
+    wsol_before: u64,
+    wrapped: u64,
+    unwrap_sol: bool,
+) -> Result<()> {
+    let wsol_after = token_balance(&wsol_account)?;
+    let spent = wsol_before
+        .saturating_add(wrapped)
+        .saturating_sub(wsol_after);
+
+    let returned = if unwrap_sol {
+        anchor_spl::token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: wsol_account,
+                destination: ctx.accounts.wallet.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+        ))?;
+        wsol_after
+    } else {
+        0
+    };
+
+    emit!(SolSettled {
+        wrapped,
+        spent,
+        returned,
+        held_before: wsol_before,
+    });
+
     Ok(())
 }
 
 /// Build CPI accounts for Raydium swap based on swap direction
 fn build_swap_cpi_accounts<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
     a_for_b: bool,
 ) -> cpi::accounts::SwapSingleV2<'info> {
-    let is_mint_a_wsol = ctx.accounts.token_mint_a.key() == WSOL_MINT;
-
-    // Determine input/output token accounts based on swap direction
-    let (input_token_account, output_token_account) = if a_for_b {
-        if is_mint_a_wsol {
-            (
-                ctx.accounts.wsol_ata.to_account_info(),
-                ctx.accounts.spl_ata.to_account_info(),
-            )
-        } else {
-            (
-                ctx.accounts.spl_ata.to_account_info(),
-                ctx.accounts.wsol_ata.to_account_info(),
-            )
-        }
-    } else {
-        if is_mint_a_wsol {
-            (
-                ctx.accounts.spl_ata.to_account_info(),
-                ctx.accounts.wsol_ata.to_account_info(),
-            )
-        } else {
-            (
-                ctx.accounts.wsol_ata.to_account_info(),
-                ctx.accounts.spl_ata.to_account_info(),
-            )
-        }
-    };
-
-    let (input_vault, output_vault, input_vault_mint, output_vault_mint) = if a_for_b {
+    let (
+        input_token_account,
+        output_token_account,
+        input_vault,
+        output_vault,
+        input_vault_mint,
+        output_vault_mint,
+    ) = if a_for_b {
         (
+            ctx.accounts.token_account_a.to_account_info(),
+            ctx.accounts.token_account_b.to_account_info(),
             ctx.accounts.token_vault_a.to_account_info(),
             ctx.accounts.token_vault_b.to_account_info(),
             ctx.accounts.token_mint_a.to_account_info(),
@@ -215,6 +443,8 @@ fn build_swap_cpi_accounts<'info>(
         )
     } else {
         (
+            ctx.accounts.token_account_b.to_account_info(),
+            ctx.accounts.token_account_a.to_account_info(),
             ctx.accounts.token_vault_b.to_account_info(),
             ctx.accounts.token_vault_a.to_account_info(),
             ctx.accounts.token_mint_b.to_account_info(),
@@ -239,67 +469,106 @@ fn build_swap_cpi_accounts<'info>(
     }
 }
 
-/// Validate that the WSOL ATA provided is correct for the wallet
-fn require_valid_wsol_ata<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
+/// Validate that the token accounts are the wallet's ATAs for the pool mints,
+/// creating any that don't exist yet
+fn require_token_accounts<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
 ) -> Result<()> {
-    let expected_wsol_ata = get_associated_token_address(&ctx.accounts.wallet.key(), &WSOL_MINT);
-    require_keys_eq!(
-        ctx.accounts.wsol_ata.key(),
-        expected_wsol_ata,
-        ErrorCode::InvalidWsolAta
-    );
+    let accounts = [
+        (&ctx.accounts.token_account_a, &ctx.accounts.token_mint_a),
+        (&ctx.accounts.token_account_b, &ctx.accounts.token_mint_b),
+    ];
+
+    for (token_account, mint) in accounts {
+        let mint_info = mint.to_account_info();
+        let token_program = if *mint_info.owner == ctx.accounts.token_program_2022.key() {
+            ctx.accounts.token_program_2022.to_account_info()
+        } else {
+            ctx.accounts.token_program.to_account_info()
+        };
+
+        let expected_ata = get_associated_token_address_with_program_id(
+            &ctx.accounts.wallet.key(),
+            &mint.key(),
+            &token_program.key(),
+        );
+        require_keys_eq!(
+            token_account.key(),
+            expected_ata,
+            ErrorCode::InvalidTokenAta
+        );
+
+        if token_account.data_is_empty() {
+            anchor_spl::associated_token::create(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: ctx.accounts.wallet.to_account_info(),
+                    associated_token: token_account.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                    mint: mint_info,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program,
+                },
+            ))?;
+        }
+    }
+
     Ok(())
 }
 
-/// Verify that the swap reached the target price within tolerance
+/// Verify that the swap reached the target price within tolerance, returning the price reached
 fn require_price_reached<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
+    ctx: &Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
     from_sqrt_price: u128,
-    to_sqrt_price: u128,
-) -> Result<()> {
-    let actual_sqrt_price = {
+    step: &PathStep,
+) -> Result<u128> {
+    let (actual_sqrt_price, tick_current) = {
         let pool = ctx.accounts.pool_state.load()?;
-        pool.sqrt_price_x64
+        (pool.sqrt_price_x64, pool.tick_current)
     };
 
-    let diff = if actual_sqrt_price > to_sqrt_price {
-        actual_sqrt_price - to_sqrt_price
-    } else {
-        to_sqrt_price - actual_sqrt_price
-    };
-
-    let tolerance = to_sqrt_price / 1000; // 0.1% tolerance
-    require!(diff <= tolerance, ErrorCode::PriceNotReached);
+    require!(
+        step.is_reached(actual_sqrt_price, tick_current),
+        ErrorCode::PriceNotReached
+    );
 
     emit!(SwapSqrt {
         from_sqrt_price,
-        to_sqrt_price,
+        to_sqrt_price: step.target_sqrt_price,
         actual_sqrt_price,
     });
 
-    Ok(())
+    Ok(actual_sqrt_price)
 }
 
 #[derive(Accounts)]
-#[instruction(to_sqrt_prices: [u128; 3], max_inputs: [u64; 3], min_outputs: [u64; 3])]
 pub struct SwapCandleSimple<'info> {
     /// Wallet that signs and pays for the transaction
     #[account(mut)]
     pub wallet: Signer<'info>,
 
-    /// Wallet's token ATA (non-SOL token)
+    /// CHECK: Wallet's ATA for token mint A - validated in function, created if missing
     #[account(mut)]
-    pub spl_ata: InterfaceAccount<'info, TokenAccount>,
+    pub token_account_a: UncheckedAccount<'info>,
 
-    /// CHECK: Wallet's WSOL ATA - validated in function
+    /// CHECK: Wallet's ATA for token mint B - validated in function, created if missing
     #[account(mut)]
-    pub wsol_ata: UncheckedAccount<'info>,
+    pub token_account_b: UncheckedAccount<'info>,
 
     /// Raydium CLMM program
     pub raydium_program: Program<'info, AmmV3>,
 
+    /// Program config holding the pool allowlist
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused,
+        constraint = program_config.is_pool_allowed(&pool_state.key()) @ ErrorCode::PoolNotAllowed
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
     /// AMM config account
+    #[account(address = pool_state.load()?.amm_config @ ErrorCode::InvalidAmmConfig)]
     pub amm_config: Box<Account<'info, AmmConfig>>,
 
     /// Pool state account
@@ -307,21 +576,23 @@ pub struct SwapCandleSimple<'info> {
     pub pool_state: AccountLoader<'info, PoolState>,
 
     /// Pool token vault A
-    #[account(mut)]
+    #[account(mut, address = pool_state.load()?.token_vault_0 @ ErrorCode::InvalidTokenVault)]
     pub token_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Pool token vault B
-    #[account(mut)]
+    #[account(mut, address = pool_state.load()?.token_vault_1 @ ErrorCode::InvalidTokenVault)]
     pub token_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Token mint A
+    #[account(address = pool_state.load()?.token_mint_0 @ ErrorCode::InvalidPoolMint)]
     pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
 
     /// Token mint B
+    #[account(address = pool_state.load()?.token_mint_1 @ ErrorCode::InvalidPoolMint)]
     pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: Raydium observation state account - validated by Raydium CPI
-    #[account(mut)]
+    /// CHECK: Raydium observation state account - must be the pool's observation account
+    #[account(mut, address = pool_state.load()?.observation_key @ ErrorCode::InvalidObservationState)]
     pub observation_state: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -336,14 +607,6 @@ pub struct SwapCandleSimple<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid WSOL ATA provided")]
-    InvalidWsolAta,
-    #[msg("Price not reached: swap did not reach target price within tolerance")]
-    PriceNotReached,
-}
-
 #[event]
 pub struct Swap {
     pub a_for_b: bool,
@@ -357,3 +620,16 @@ pub struct SwapSqrt {
     pub to_sqrt_price: u128,
     pub actual_sqrt_price: u128,
 }
+
+#[event]
+pub struct SolSettled {
+    /// Lamports wrapped into the WSOL ATA over the whole path
+    pub wrapped: u64,
+    /// Net WSOL that left the ATA over the whole path
+    pub spent: u64,
+    /// WSOL unwrapped back to the wallet as native SOL: the ATA's whole
+    /// balance, `held_before + wrapped - spent`, or 0 without `unwrap_sol`
+    pub returned: u64,
+    /// WSOL the ATA already held before the first step
+    pub held_before: u64,
+}
@@ -1,16 +1,18 @@
+use crate::errors::ErrorCode;
 use crate::state::TradingAccount;
 use anchor_lang::prelude::*;
 use anchor_spl::token;
 use anchor_spl::token_2022;
 use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 
-/// Withdraw tokens from PDA-owned token account to user's wallet
+/// Withdraw tokens from the token account of trading account `id` to user's wallet
 ///
 /// This transfers tokens from the PDA-owned token account back to the user.
 /// PDA signs the transfer using its seeds.
 /// Works with both SPL Token and Token-2022, including WSOL.
 /// Use this for withdrawing already-wrapped WSOL.
-pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+/// Only the owner can withdraw; operators are limited to swaps.
+pub fn withdraw(ctx: Context<Withdraw>, id: u16, amount: u64) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
 
     // Check sufficient balance
@@ -21,7 +23,8 @@ pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
     let bump = ctx.accounts.trading_account.bump;
 
     // PDA seeds for signing
-    let seeds = &[b"trading_account", user_key.as_ref(), &[bump]];
+    let id_seed = TradingAccount::id_seed(id);
+    let seeds = &[b"trading_account", user_key.as_ref(), &id_seed, &[bump]];
     let signer = &[&seeds[..]];
 
     // Transfer tokens from PDA-owned token account to user's token account
@@ -40,17 +43,29 @@ pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         ctx.accounts.mint.decimals,
     )?;
 
+    // Balances the ledger never saw (sent straight to a PDA token account, or
+    // held before migration) are withdrawn without being recorded
+    if let Some(entry) = ctx
+        .accounts
+        .trading_account
+        .tracked_ledger_mut(&ctx.accounts.mint.key())
+    {
+        entry.record_withdraw(amount)?;
+    }
+
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(id: u16)]
 pub struct Withdraw<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
     /// The user's trading account PDA (for validation)
     #[account(
-        seeds = [b"trading_account", user.key().as_ref()],
+        mut,
+        seeds = [b"trading_account", user.key().as_ref(), &TradingAccount::id_seed(id)],
         bump = trading_account.bump,
         constraint = trading_account.owner  == user.key() @ ErrorCode::Unauthorized
     )]
@@ -84,19 +99,3 @@ pub struct Withdraw<'info> {
     )]
     pub token_program: Interface<'info, TokenInterface>,
 }
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Amount must be greater than 0")]
-    InvalidAmount,
-    #[msg("Insufficient balance in trading account")]
-    InsufficientBalance,
-    #[msg("Unauthorized: You don't own this trading account")]
-    Unauthorized,
-    #[msg("Arithmetic underflow")]
-    Underflow,
-    #[msg("Invalid token program: must be SPL Token or Token-2022")]
-    InvalidTokenProgram,
-    #[msg("Invalid token account owner: must be owned by the token program")]
-    InvalidTokenAccountOwner,
-}
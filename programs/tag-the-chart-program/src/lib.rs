@@ -1,9 +1,18 @@
 use anchor_lang::prelude::*;
 
+mod candle;
+pub mod errors;
 mod instructions;
-mod state;
+pub mod path;
+pub mod pnl;
+pub mod price;
+mod quote;
+pub mod state;
+mod utils;
 
 use instructions::*;
+use path::{OpenPriceGuard, SpendLimits};
+use price::{DecimalPrices, PriceOffsets};
 
 declare_id!("47z6kVAxM8LxGqSgFHXyMq3eK4Lq2U7TQXLpV3bjPtdD");
 
@@ -11,43 +20,298 @@ declare_id!("47z6kVAxM8LxGqSgFHXyMq3eK4Lq2U7TQXLpV3bjPtdD");
 pub mod tag_the_chart_program {
     use super::*;
 
-    /// Initialize a trading account (PDA) for a user
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize::initialize(ctx)
+    /// Initialize trading account `id` (PDA) for a user - 0 is the original account
+    pub fn initialize(ctx: Context<Initialize>, id: u16) -> Result<()> {
+        instructions::initialize::initialize(ctx, id)
     }
 
-    /// Execute a candle pattern (3 atomic swaps) - Simple wallet version with auto-wrap
+    /// Upgrade the caller's legacy (unversioned) trading account to the current layout
+    pub fn migrate_trading_account(ctx: Context<MigrateTradingAccount>) -> Result<()> {
+        instructions::migrate_trading_account::migrate_trading_account(ctx)
+    }
+
+    /// Track cost basis and realized PnL of a base token in quote terms across swaps
+    pub fn configure_pnl_pair(
+        ctx: Context<ConfigurePnlPair>,
+        id: u16,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+    ) -> Result<()> {
+        instructions::configure_pnl_pair::configure_pnl_pair(ctx, id, base_mint, quote_mint)
+    }
+
+    /// Allow a delegated key to swap (never withdraw) for the caller's trading account
+    pub fn add_operator(
+        ctx: Context<AddOperator>,
+        id: u16,
+        operator: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::operators::add_operator(ctx, id, operator, expires_at)
+    }
+
+    /// Revoke a delegated key of the caller's trading account
+    pub fn remove_operator(ctx: Context<RemoveOperator>, id: u16, operator: Pubkey) -> Result<()> {
+        instructions::operators::remove_operator(ctx, id, operator)
+    }
+
+    /// Track every token account of a migrated trading account in its ledger
+    pub fn rebuild_ledger(ctx: Context<RebuildLedger>, id: u16) -> Result<()> {
+        instructions::rebuild_ledger::rebuild_ledger(ctx, id)
+    }
+
+    /// Withdraw every passed token balance, close the token accounts and the trading account
+    pub fn close_trading_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseTradingAccount<'info>>,
+        id: u16,
+    ) -> Result<()> {
+        instructions::close_trading_account::close_trading_account(ctx, id)
+    }
+
+    /// Initialize the program config (PDA) - upgrade authority only
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        admin: Pubkey,
+        guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_config::initialize_config(ctx, admin, guardian)
+    }
+
+    /// Pause or resume all swaps - admin or guardian
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::pause::set_paused(ctx, paused)
+    }
+
+    /// Replace the guardian key - admin only
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::pause::set_guardian(ctx, guardian)
+    }
+
+    /// Allow swaps on a Raydium CLMM pool - admin only
+    pub fn add_pool(ctx: Context<AddPool>) -> Result<()> {
+        instructions::pool_allowlist::add_pool(ctx)
+    }
+
+    /// Stop allowing swaps on a pool - admin only
+    pub fn remove_pool(ctx: Context<RemovePool>, pool: Pubkey) -> Result<()> {
+        instructions::pool_allowlist::remove_pool(ctx, pool)
+    }
+
+    /// Execute a candle pattern (one atomic swap per path step) - Simple wallet version with auto-wrap
+    #[allow(clippy::too_many_arguments)]
     pub fn swap_to_prices_simple<'info>(
-        ctx: Context<'_, '_, '_, 'info, SwapCandleSimple<'info>>,
-        target_sqrt_prices: [u128; 3],
-        max_inputs: [u64; 3],
-        min_outputs: [u64; 3],
+        ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+        target_sqrt_prices: Vec<u128>,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        unwrap_sol: bool,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
     ) -> Result<()> {
         instructions::swap_simple::swap_to_prices_simple(
             ctx,
             target_sqrt_prices,
             max_inputs,
             min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            unwrap_sol,
+            open_guard,
+            spend_limits,
         )
     }
 
-    // /// Execute a candle pattern (3 atomic swaps) - PDA version
-    // pub fn swap_to_prices<'info>(
-    //     ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
-    //     target_sqrt_prices: [u128; 3],
-    //     max_inputs: [u64; 3],
-    //     min_outputs: [u64; 3],
-    // ) -> Result<()> {
-    //     instructions::swap::swap_to_prices(ctx, target_sqrt_prices, max_inputs, min_outputs)
-    // }
+    /// Execute a candle pattern (one atomic swap per path step) - PDA version
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_prices<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+        id: u16,
+        target_sqrt_prices: Vec<u128>,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap::swap_to_prices(
+            ctx,
+            id,
+            target_sqrt_prices,
+            max_inputs,
+            min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            open_guard,
+            spend_limits,
+        )
+    }
 
-    // /// Deposit tokens (SPL Token or Token-2022, including WSOL)
-    // pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-    //     instructions::deposit::deposit(ctx, amount)
-    // }
+    /// Execute a candle pattern with targets as decimal prices - Simple wallet version with auto-wrap
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_decimal_prices_simple<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+        target_prices: DecimalPrices,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        unwrap_sol: bool,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap_simple::swap_to_decimal_prices_simple(
+            ctx,
+            target_prices,
+            max_inputs,
+            min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            unwrap_sol,
+            open_guard,
+            spend_limits,
+        )
+    }
 
-    // /// Withdraw tokens (SPL Token or Token-2022, including WSOL)
-    // pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-    //     instructions::withdraw::withdraw(ctx, amount)
-    // }
+    /// Execute a candle pattern with targets as decimal prices - PDA version
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_decimal_prices<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+        id: u16,
+        target_prices: DecimalPrices,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap::swap_to_decimal_prices(
+            ctx,
+            id,
+            target_prices,
+            max_inputs,
+            min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            open_guard,
+            spend_limits,
+        )
+    }
+
+    /// Execute a candle pattern with targets as offsets from the open price - Simple wallet version with auto-wrap
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_offsets_simple<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+        target_offsets: PriceOffsets,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        unwrap_sol: bool,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap_simple::swap_to_offsets_simple(
+            ctx,
+            target_offsets,
+            max_inputs,
+            min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            unwrap_sol,
+            open_guard,
+            spend_limits,
+        )
+    }
+
+    /// Execute a candle pattern with targets as offsets from the open price - PDA version
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_offsets<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+        id: u16,
+        target_offsets: PriceOffsets,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        tolerances_bps: Vec<u16>,
+        exact_outputs: Vec<bool>,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap::swap_to_offsets(
+            ctx,
+            id,
+            target_offsets,
+            max_inputs,
+            min_outputs,
+            tolerances_bps,
+            exact_outputs,
+            open_guard,
+            spend_limits,
+        )
+    }
+
+    /// Execute a candle pattern with targets as tick indexes - Simple wallet version with auto-wrap
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_to_ticks_simple<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapCandleSimple<'info>>,
+        target_ticks: Vec<i32>,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        exact_outputs: Vec<bool>,
+        unwrap_sol: bool,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap_simple::swap_to_ticks_simple(
+            ctx,
+            target_ticks,
+            max_inputs,
+            min_outputs,
+            exact_outputs,
+            unwrap_sol,
+            open_guard,
+            spend_limits,
+        )
+    }
+
+    /// Execute a candle pattern with targets as tick indexes - PDA version
+    pub fn swap_to_ticks<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCandle<'info>>,
+        id: u16,
+        target_ticks: Vec<i32>,
+        max_inputs: Vec<u64>,
+        min_outputs: Vec<u64>,
+        exact_outputs: Vec<bool>,
+        open_guard: Option<OpenPriceGuard>,
+        spend_limits: Option<SpendLimits>,
+    ) -> Result<()> {
+        instructions::swap::swap_to_ticks(
+            ctx,
+            id,
+            target_ticks,
+            max_inputs,
+            min_outputs,
+            exact_outputs,
+            open_guard,
+            spend_limits,
+        )
+    }
+
+    /// Deposit tokens (SPL Token or Token-2022, including WSOL)
+    pub fn deposit(ctx: Context<Deposit>, id: u16, amount: u64) -> Result<()> {
+        instructions::deposit::deposit(ctx, id, amount)
+    }
+
+    /// Deposit native SOL, wrapped into the trading account's WSOL ATA
+    pub fn deposit_sol(ctx: Context<DepositSol>, id: u16, amount: u64) -> Result<()> {
+        instructions::deposit_sol::deposit_sol(ctx, id, amount)
+    }
+
+    /// Withdraw tokens (SPL Token or Token-2022, including WSOL)
+    pub fn withdraw(ctx: Context<Withdraw>, id: u16, amount: u64) -> Result<()> {
+        instructions::withdraw::withdraw(ctx, id, amount)
+    }
 }
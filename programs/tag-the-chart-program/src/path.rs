@@ -0,0 +1,236 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use raydium_amm_v3::libraries::tick_math::{
+    self, MAX_SQRT_PRICE_X64, MAX_TICK, MIN_SQRT_PRICE_X64, MIN_TICK,
+};
+
+/// Default price-reached tolerance, in basis points of the target sqrt price (0.1%)
+pub const DEFAULT_PRICE_TOLERANCE_BPS: u16 = 10;
+
+/// Basis points denominator
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Optional stale-quote guard on the pool price the path starts from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenPriceGuard {
+    /// Sqrt price (X64 format) the path was planned from
+    pub expected_sqrt_price: u128,
+    /// Allowed drift of the actual open from `expected_sqrt_price`, in basis points of it
+    /// (at most 10000)
+    pub max_drift_bps: u16,
+}
+
+impl OpenPriceGuard {
+    /// Abort if the pool moved too far from the expected open before the first step
+    pub fn check(&self, open_sqrt_price: u128) -> Result<()> {
+        require!(
+            self.max_drift_bps <= BPS_DENOMINATOR,
+            ErrorCode::InvalidTolerance
+        );
+        let max_drift = self
+            .expected_sqrt_price
+            .checked_mul(u128::from(self.max_drift_bps))
+            .ok_or(error!(ErrorCode::Overflow))?
+            / u128::from(BPS_DENOMINATOR);
+        require!(
+            open_sqrt_price.abs_diff(self.expected_sqrt_price) <= max_drift,
+            ErrorCode::OpenPriceDrift
+        );
+        Ok(())
+    }
+}
+
+/// Optional caps on what the whole path may cost, net of what it bought back
+///
+/// Limits are in raw token units and `0` means no limit. `base_is_a` says
+/// whether token A or token B is the base token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpendLimits {
+    pub base_is_a: bool,
+    pub max_net_base_spent: u64,
+    pub max_net_quote_spent: u64,
+}
+
+impl SpendLimits {
+    /// Compare `(a, b)` balances after the path with the snapshot taken before it
+    ///
+    /// `added` is what was put into each account from outside the pool during
+    /// the path (SOL wrapped on the fly), which counts as spent.
+    pub fn check(&self, before: (u64, u64), after: (u64, u64), added: (u64, u64)) -> Result<()> {
+        let net_spent = |before: u64, after: u64, added: u64| {
+            i128::from(before) + i128::from(added) - i128::from(after)
+        };
+        let net_spent_a = net_spent(before.0, after.0, added.0);
+        let net_spent_b = net_spent(before.1, after.1, added.1);
+        let (net_base_spent, net_quote_spent) = if self.base_is_a {
+            (net_spent_a, net_spent_b)
+        } else {
+            (net_spent_b, net_spent_a)
+        };
+
+        let within = |net_spent: i128, limit: u64| limit == 0 || net_spent <= i128::from(limit);
+        require!(
+            within(net_base_spent, self.max_net_base_spent)
+                && within(net_quote_spent, self.max_net_quote_spent),
+            ErrorCode::NetSpendExceeded
+        );
+        Ok(())
+    }
+}
+
+/// A single leg of a price path
+#[derive(Clone, Copy, Debug)]
+pub struct PathStep {
+    /// Target sqrt price (X64 format)
+    pub target_sqrt_price: u128,
+    /// Max input amount (0 = no limit)
+    pub max_input: u64,
+    /// Min output amount (0 = no limit), or the exact output in exact-output mode
+    pub min_output: u64,
+    /// Buy exactly `min_output`, using the target only as a price limit
+    pub exact_output: bool,
+    /// Allowed distance from the target, in basis points of the target sqrt price
+    pub tolerance_bps: u16,
+    /// Tick the pool must land on exactly, replacing the tolerance check
+    pub target_tick: Option<i32>,
+}
+
+impl PathStep {
+    /// `(amount, other_amount_threshold)` for Raydium `swap_v2` in this step's mode
+    pub fn swap_amounts(&self) -> (u64, u64) {
+        let max_input = if self.max_input == 0 {
+            u64::MAX
+        } else {
+            self.max_input
+        };
+        if self.exact_output {
+            (self.min_output, max_input)
+        } else {
+            (max_input, self.min_output)
+        }
+    }
+
+    /// Whether the pool landed close enough to this step's target
+    ///
+    /// Exact-output steps may stop short of the target once the output is
+    /// filled, so only exact-input steps are expected to land on it.
+    ///
+    /// Tick targets must match `tick_current` exactly. A pool that crossed the
+    /// target tick downward sits exactly on its sqrt price with the tick just
+    /// below, which Raydium reports as `target - 1`, so that counts as well.
+    pub fn is_reached(&self, actual_sqrt_price: u128, tick_current: i32) -> bool {
+        if self.exact_output {
+            return true;
+        }
+        if let Some(target_tick) = self.target_tick {
+            return tick_current == target_tick
+                || (tick_current == target_tick - 1
+                    && actual_sqrt_price == self.target_sqrt_price);
+        }
+        // Targets are below MAX_SQRT_PRICE_X64 (about 2^96), see `build_path`,
+        // so this cannot overflow
+        let tolerance =
+            self.target_sqrt_price * u128::from(self.tolerance_bps) / u128::from(BPS_DENOMINATOR);
+        actual_sqrt_price.abs_diff(self.target_sqrt_price) <= tolerance
+    }
+}
+
+/// Zip the per-step instruction arguments into path steps
+///
+/// The path length is bounded only by the compute budget, but every
+/// argument array must have one entry per step, and every target must be a
+/// sqrt price Raydium accepts. `tolerances_bps` may also be left empty to
+/// apply `DEFAULT_PRICE_TOLERANCE_BPS` to every step, and `exact_outputs` left
+/// empty to run every step in exact-input mode.
+pub fn build_path(
+    target_sqrt_prices: &[u128],
+    max_inputs: &[u64],
+    min_outputs: &[u64],
+    tolerances_bps: &[u16],
+    exact_outputs: &[bool],
+) -> Result<Vec<PathStep>> {
+    let len = target_sqrt_prices.len();
+    require!(len > 0, ErrorCode::EmptyPath);
+    require!(
+        max_inputs.len() == len
+            && min_outputs.len() == len
+            && (tolerances_bps.is_empty() || tolerances_bps.len() == len)
+            && (exact_outputs.is_empty() || exact_outputs.len() == len),
+        ErrorCode::PathLengthMismatch
+    );
+    require!(
+        target_sqrt_prices
+            .iter()
+            .all(|target| (MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64).contains(target)),
+        ErrorCode::PriceOutOfRange
+    );
+    require!(
+        tolerances_bps.iter().all(|&bps| bps <= BPS_DENOMINATOR),
+        ErrorCode::InvalidTolerance
+    );
+    require!(
+        exact_outputs
+            .iter()
+            .zip(min_outputs)
+            .all(|(&exact_output, &min_output)| !exact_output || min_output > 0),
+        ErrorCode::InvalidExactOutput
+    );
+
+    Ok((0..len)
+        .map(|i| PathStep {
+            target_sqrt_price: target_sqrt_prices[i],
+            max_input: max_inputs[i],
+            min_output: min_outputs[i],
+            exact_output: exact_outputs.get(i).copied().unwrap_or(false),
+            tolerance_bps: tolerances_bps
+                .get(i)
+                .copied()
+                .unwrap_or(DEFAULT_PRICE_TOLERANCE_BPS),
+            target_tick: None,
+        })
+        .collect())
+}
+
+/// Build a path whose steps target tick indexes instead of sqrt prices
+///
+/// Every tick must be a multiple of the pool's `tick_spacing` and within
+/// `MIN_TICK..=MAX_TICK`. Steps are swapped up to the tick's sqrt price and
+/// checked against `tick_current` rather than a price tolerance.
+pub fn build_tick_path(
+    target_ticks: &[i32],
+    tick_spacing: u16,
+    max_inputs: &[u64],
+    min_outputs: &[u64],
+    exact_outputs: &[bool],
+) -> Result<Vec<PathStep>> {
+    require!(
+        target_ticks
+            .iter()
+            .all(|tick| (MIN_TICK..=MAX_TICK).contains(tick)),
+        ErrorCode::TickOutOfRange
+    );
+    require!(
+        target_ticks
+            .iter()
+            .all(|tick| tick % i32::from(tick_spacing) == 0),
+        ErrorCode::TickNotAligned
+    );
+
+    let target_sqrt_prices = target_ticks
+        .iter()
+        .map(|&tick| tick_math::get_sqrt_price_at_tick(tick))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut path = build_path(
+        &target_sqrt_prices,
+        max_inputs,
+        min_outputs,
+        &[],
+        exact_outputs,
+    )?;
+    for (step, &tick) in path.iter_mut().zip(target_ticks) {
+        step.target_tick = Some(tick);
+    }
+
+    Ok(path)
+}
@@ -0,0 +1,105 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use raydium_amm_v3::libraries::big_num::U256;
+
+/// Cost basis and realized PnL of the base token of one pair, valued in the
+/// quote token
+///
+/// Only base bought by tracked swaps carries a cost basis. Base sold beyond
+/// the position (deposited base, for example) has no known cost and is left
+/// out of the realized PnL.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PnlPosition {
+    /// Token whose cost basis is tracked; all zeros while no pair is configured
+    pub base_mint: Pubkey,
+
+    /// Token the cost basis and PnL are valued in
+    pub quote_mint: Pubkey,
+
+    /// Base bought by swaps and not sold yet, in base units
+    pub position: u64,
+
+    /// Quote paid for `position`, fees included, in quote units
+    pub cost_basis: u128,
+
+    /// Quote received for sold base minus the cost basis it carried
+    pub realized_pnl: i128,
+}
+
+impl PnlPosition {
+    /// Size of the serialized position
+    /// 32 (base_mint) + 32 (quote_mint) + 8 (position) + 16 (cost_basis) + 16 (realized_pnl)
+    pub const LEN: usize = 32 + 32 + 8 + 16 + 16;
+
+    pub fn new(base_mint: Pubkey, quote_mint: Pubkey) -> Self {
+        Self {
+            base_mint,
+            quote_mint,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.base_mint != Pubkey::default()
+    }
+
+    /// Update the position with one swap leg from its actual amounts,
+    /// returning whether the leg traded the tracked pair
+    pub fn record_fill(
+        &mut self,
+        mint_in: Pubkey,
+        amount_in: u64,
+        mint_out: Pubkey,
+        amount_out: u64,
+    ) -> Result<bool> {
+        if !self.is_configured() {
+            return Ok(false);
+        }
+
+        if mint_in == self.quote_mint && mint_out == self.base_mint {
+            // Buy: the quote paid becomes the cost basis of the base received
+            self.position = self
+                .position
+                .checked_add(amount_out)
+                .ok_or(ErrorCode::Overflow)?;
+            self.cost_basis = self
+                .cost_basis
+                .checked_add(u128::from(amount_in))
+                .ok_or(ErrorCode::Overflow)?;
+        } else if mint_in == self.base_mint && mint_out == self.quote_mint {
+            // Sell: release the average cost of the base sold out of the position
+            let sold = amount_in.min(self.position);
+            if sold > 0 {
+                let cost = mul_div(self.cost_basis, sold, self.position);
+                let proceeds = mul_div(u128::from(amount_out), sold, amount_in);
+                let pnl = i128::try_from(proceeds).map_err(|_| ErrorCode::Overflow)?
+                    - i128::try_from(cost).map_err(|_| ErrorCode::Overflow)?;
+
+                self.realized_pnl = self
+                    .realized_pnl
+                    .checked_add(pnl)
+                    .ok_or(ErrorCode::Overflow)?;
+                self.position -= sold;
+                self.cost_basis -= cost;
+            }
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Average quote paid per base unit held, as a Q64.64 number (0 without a position)
+    pub fn average_entry_price_x64(&self) -> u128 {
+        if self.position == 0 {
+            return 0;
+        }
+        let price = (U256::from(self.cost_basis) << 64) / U256::from(self.position);
+        price.min(U256::from(u128::MAX)).as_u128()
+    }
+}
+
+/// `value * numerator / denominator`, for `numerator <= denominator`
+fn mul_div(value: u128, numerator: u64, denominator: u64) -> u128 {
+    (U256::from(value) * U256::from(numerator) / U256::from(denominator)).as_u128()
+}
@@ -0,0 +1,134 @@
+use crate::errors::ErrorCode;
+use crate::path::BPS_DENOMINATOR;
+use anchor_lang::prelude::*;
+use raydium_amm_v3::libraries::{
+    big_num::U256,
+    tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64},
+};
+
+/// Target prices as decimal-scaled integers, quoted in human units
+///
+/// Each price is `prices[i] / 10^exponent` quote tokens per base token. With
+/// `base_is_a` token A is the base and B the quote; otherwise it is the
+/// other way round.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DecimalPrices {
+    pub prices: Vec<u64>,
+    pub exponent: u8,
+    pub base_is_a: bool,
+}
+
+impl DecimalPrices {
+    /// Convert every price to a Raydium sqrt price (X64 format)
+    pub fn to_sqrt_prices(&self, decimals_a: u8, decimals_b: u8) -> Result<Vec<u128>> {
+        self.prices
+            .iter()
+            .map(|&price| {
+                price_to_sqrt_price_x64(
+                    price,
+                    self.exponent,
+                    decimals_a,
+                    decimals_b,
+                    self.base_is_a,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Target prices as signed basis-point offsets from the open sqrt price
+///
+/// Offsets apply to the sqrt price read at the start of the instruction, so a
+/// transaction signed a few slots early still draws the same shape. With
+/// `pin_close_to_open` the last step targets the open price exactly,
+/// whatever its offset.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PriceOffsets {
+    pub offsets_bps: Vec<i32>,
+    pub pin_close_to_open: bool,
+}
+
+impl PriceOffsets {
+    /// Resolve every offset against `open_sqrt_price`
+    pub fn to_sqrt_prices(&self, open_sqrt_price: u128) -> Result<Vec<u128>> {
+        let mut sqrt_prices = self
+            .offsets_bps
+            .iter()
+            .map(|&offset_bps| offset_sqrt_price(open_sqrt_price, offset_bps))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.pin_close_to_open {
+            if let Some(close) = sqrt_prices.last_mut() {
+                *close = open_sqrt_price;
+            }
+        }
+
+        Ok(sqrt_prices)
+    }
+}
+
+/// `sqrt_price * (10000 + offset_bps) / 10000`, kept within Raydium's price range
+fn offset_sqrt_price(sqrt_price: u128, offset_bps: i32) -> Result<u128> {
+    let scale = i64::from(BPS_DENOMINATOR) + i64::from(offset_bps);
+    require!(scale > 0, ErrorCode::InvalidOffset);
+
+    let target = U256::from(sqrt_price) * U256::from(scale as u64) / U256::from(BPS_DENOMINATOR);
+    require!(
+        target >= U256::from(MIN_SQRT_PRICE_X64) && target < U256::from(MAX_SQRT_PRICE_X64),
+        ErrorCode::PriceOutOfRange
+    );
+
+    Ok(target.as_u128())
+}
+
+/// Convert a decimal-scaled price to a Raydium sqrt price (X64 format)
+///
+/// Raydium prices are raw token B units per raw token A unit, so the human
+/// price is rescaled by the mint decimals (and inverted when B is the base)
+/// before taking the square root. The ratio is shifted left by 128 bits
+/// first, so the integer square root is already in X64 format.
+pub fn price_to_sqrt_price_x64(
+    price: u64,
+    exponent: u8,
+    decimals_a: u8,
+    decimals_b: u8,
+    base_is_a: bool,
+) -> Result<u128> {
+    require!(price > 0, ErrorCode::PriceOutOfRange);
+
+    // raw B per A = price * 10^decimals_b / (10^exponent * 10^decimals_a)
+    let price = U256::from(price);
+    let (numerator, denominator) = if base_is_a {
+        (
+            price.checked_mul(pow10(decimals_b)?),
+            pow10(exponent)?.checked_mul(pow10(decimals_a)?),
+        )
+    } else {
+        (
+            pow10(exponent)?.checked_mul(pow10(decimals_b)?),
+            price.checked_mul(pow10(decimals_a)?),
+        )
+    };
+    let numerator = numerator.ok_or(ErrorCode::PriceConversionOverflow)?;
+    let denominator = denominator.ok_or(ErrorCode::PriceConversionOverflow)?;
+
+    let ratio_x128 = numerator
+        .checked_mul(U256::one() << 128)
+        .ok_or(ErrorCode::PriceConversionOverflow)?
+        / denominator;
+    let sqrt_price_x64 = ratio_x128.integer_sqrt();
+
+    require!(
+        sqrt_price_x64 >= U256::from(MIN_SQRT_PRICE_X64)
+            && sqrt_price_x64 < U256::from(MAX_SQRT_PRICE_X64),
+        ErrorCode::PriceOutOfRange
+    );
+
+    Ok(sqrt_price_x64.as_u128())
+}
+
+fn pow10(exponent: u8) -> Result<U256> {
+    U256::from(10)
+        .checked_pow(U256::from(exponent))
+        .ok_or_else(|| error!(ErrorCode::PriceConversionOverflow))
+}
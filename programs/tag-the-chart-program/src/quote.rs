@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use raydium_amm_v3::{
+    libraries::{liquidity_math, swap_math, tick_math},
+    states::{AmmConfig, PoolState, TickArrayState, TICK_ARRAY_SIZE_USIZE},
+};
+
+/// Amounts Raydium will move when swapping from the current pool price to a target price
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SwapQuote {
+    /// Total input, including fees
+    pub amount_in: u64,
+    /// Total output
+    pub amount_out: u64,
+    /// Fees paid out of `amount_in`
+    pub fee_amount: u64,
+    /// Sqrt price (X64 format) the swap ends at
+    pub sqrt_price_x64: u128,
+}
+
+/// Quote a swap up to `to_sqrt_price`, walking every initialized tick on the way
+///
+/// With `is_base_input` the quote spends at most `amount` (fees included);
+/// otherwise it buys at most `amount` of the output token. Ticks are read from
+/// the tick arrays in `tick_arrays` (usually the instruction's
+/// `remaining_accounts`). Accounts that are not tick arrays of this pool are
+/// ignored, so the bitmap extension can be passed alongside them. Past the last
+/// known tick the quote continues with the liquidity in range at that point.
+pub fn quote_to_price<'info>(
+    pool_state: &AccountLoader<'info, PoolState>,
+    amm_config: &AmmConfig,
+    tick_arrays: &'info [AccountInfo<'info>],
+    to_sqrt_price: u128,
+    a_for_b: bool,
+    amount: u64,
+    is_base_input: bool,
+) -> Result<SwapQuote> {
+    let fee_rate = amm_config.trade_fee_rate;
+    let block_timestamp = Clock::get()?.unix_timestamp as u32;
+
+    let pool = pool_state.load()?;
+    let ticks = load_initialized_ticks(pool_state.key(), pool.tick_current, tick_arrays, a_for_b)?;
+
+    let mut quote = SwapQuote {
+        sqrt_price_x64: pool.sqrt_price_x64,
+        ..SwapQuote::default()
+    };
+    let mut liquidity = pool.liquidity;
+    let mut amount_remaining = amount;
+
+    // Walk tick by tick, then finish the remainder on the last known liquidity
+    let mut next_ticks = ticks.iter().map(Some).chain(std::iter::once(None));
+    while amount_remaining > 0 && quote.sqrt_price_x64 != to_sqrt_price {
+        let Some(next_tick) = next_ticks.next() else {
+            break;
+        };
+
+        let (step_target, tick_sqrt_price) = match next_tick {
+            Some((tick, _)) => {
+                let tick_sqrt_price = tick_math::get_sqrt_price_at_tick(*tick)?;
+                let step_target = if a_for_b {
+                    tick_sqrt_price.max(to_sqrt_price)
+                } else {
+                    tick_sqrt_price.min(to_sqrt_price)
+                };
+                (step_target, Some(tick_sqrt_price))
+            }
+            None => (to_sqrt_price, None),
+        };
+
+        let swap_step = swap_math::compute_swap_step(
+            quote.sqrt_price_x64,
+            step_target,
+            liquidity,
+            amount_remaining,
+            fee_rate,
+            is_base_input,
+            a_for_b,
+            block_timestamp,
+        )?;
+
+        let step_amount_in = swap_step.amount_in.saturating_add(swap_step.fee_amount);
+        amount_remaining = if is_base_input {
+            amount_remaining.saturating_sub(step_amount_in)
+        } else {
+            amount_remaining.saturating_sub(swap_step.amount_out)
+        };
+        quote.amount_in = quote.amount_in.saturating_add(step_amount_in);
+        quote.amount_out = quote.amount_out.saturating_add(swap_step.amount_out);
+        quote.fee_amount = quote.fee_amount.saturating_add(swap_step.fee_amount);
+        quote.sqrt_price_x64 = swap_step.sqrt_price_next_x64;
+
+        // Cross the tick only if the step actually reached it
+        match (next_tick, tick_sqrt_price) {
+            (Some((_, liquidity_net)), Some(tick_sqrt_price))
+                if quote.sqrt_price_x64 == tick_sqrt_price =>
+            {
+                let liquidity_delta = if a_for_b {
+                    -*liquidity_net
+                } else {
+                    *liquidity_net
+                };
+                liquidity = liquidity_math::add_delta(liquidity, liquidity_delta)?;
+            }
+            _ => break,
+        }
+    }
+
+    Ok(quote)
+}
+
+/// Collect `(tick, liquidity_net)` for every initialized tick in the swap direction,
+/// ordered the way Raydium crosses them
+fn load_initialized_ticks<'info>(
+    pool_key: Pubkey,
+    tick_current: i32,
+    tick_arrays: &'info [AccountInfo<'info>],
+    a_for_b: bool,
+) -> Result<Vec<(i32, i128)>> {
+    let mut ticks = Vec::new();
+
+    for account_info in tick_arrays.iter() {
+        let Ok(loader) = AccountLoader::<TickArrayState>::try_from(account_info) else {
+            continue;
+        };
+        let tick_array = loader.load()?;
+        if tick_array.pool_id != pool_key {
+            continue;
+        }
+
+        for i in 0..TICK_ARRAY_SIZE_USIZE {
+            let tick_state = tick_array.ticks[i];
+            if tick_state.liquidity_gross == 0 {
+                continue;
+            }
+
+            // Raydium searches down from the current tick inclusive, and up from it exclusive
+            let in_direction = if a_for_b {
+                tick_state.tick <= tick_current
+            } else {
+                tick_state.tick > tick_current
+            };
+            if in_direction {
+                ticks.push((tick_state.tick, tick_state.liquidity_net));
+            }
+        }
+    }
+
+    if a_for_b {
+        ticks.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+    } else {
+        ticks.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    }
+    ticks.dedup_by_key(|(tick, _)| *tick);
+
+    Ok(ticks)
+}
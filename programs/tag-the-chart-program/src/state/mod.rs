@@ -1,3 +1,5 @@
+pub mod program_config;
 pub mod trading_account;
 
+pub use program_config::*;
 pub use trading_account::*;
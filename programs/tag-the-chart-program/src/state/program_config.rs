@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+/// Program Config PDA - program-wide settings managed by the admin
+/// Seeds: ["program_config"]
+
+#[account]
+pub struct ProgramConfig {
+    /// Authority allowed to manage the config
+    pub admin: Pubkey,
+
+    /// Key allowed to pause and unpause swaps alongside the admin
+    pub guardian: Pubkey,
+
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+
+    /// Emergency switch: while set, every swap instruction is rejected
+    pub paused: bool,
+
+    /// Raydium CLMM pools the swap instructions may trade on
+    pub pools: Vec<Pubkey>,
+}
+
+impl ProgramConfig {
+    /// Maximum number of allowlisted pools
+    pub const MAX_POOLS: usize = 64;
+
+    /// Size calculation for account allocation
+    /// 8 (discriminator) + 32 (admin) + 32 (guardian) + 1 (bump) + 1 (paused)
+    /// + 4 (vec length) + 32 * MAX_POOLS (pools)
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 1 + 4 + 32 * Self::MAX_POOLS;
+
+    /// Whether `key` may flip the pause switch
+    pub fn can_pause(&self, key: &Pubkey) -> bool {
+        *key == self.admin || *key == self.guardian
+    }
+
+    /// Whether swaps may trade on `pool`
+    pub fn is_pool_allowed(&self, pool: &Pubkey) -> bool {
+        self.pools.contains(pool)
+    }
+}
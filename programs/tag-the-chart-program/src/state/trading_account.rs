@@ -1,3 +1,5 @@
+use crate::errors::ErrorCode;
+use crate::pnl::PnlPosition;
 use anchor_lang::prelude::*;
 
 /// Trading Account PDA - holds user funds for pattern execution
@@ -10,10 +12,265 @@ pub struct TradingAccount {
 
     /// Bump seed for PDA derivation
     pub bump: u8,
+
+    /// Layout version, see `TradingAccount::CURRENT_VERSION`
+    pub version: u8,
+
+    /// Index of this account among the owner's trading accounts
+    pub id: u16,
+
+    /// Cost basis and realized PnL of the pair set by `configure_pnl_pair`
+    pub pnl: PnlPosition,
+
+    /// Set by `migrate_trading_account`: the ledger of a migrated account
+    /// starts empty, so it cannot be closed until `rebuild_ledger` has added
+    /// every mint it holds
+    pub ledger_rebuild_pending: bool,
+
+    /// Space for new fixed-size fields, so they can be added without moving
+    /// the fields after them
+    pub reserved: [u8; 23],
+
+    /// Keys allowed to swap with the account's funds, but never to withdraw them
+    pub operators: Vec<Operator>,
+
+    /// Lifetime totals for every mint the account has moved, so a strategy's
+    /// economics can be audited from chain state alone
+    pub ledger: Vec<MintLedger>,
+}
+
+/// Delegated key that may call the swap instructions for the owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Operator {
+    pub key: Pubkey,
+
+    /// Unix timestamp after which the key can no longer swap; 0 = never expires
+    pub expires_at: i64,
+}
+
+impl Operator {
+    /// Size of one serialized operator
+    /// 32 (key) + 8 (expires_at)
+    pub const LEN: usize = 32 + 8;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+/// Lifetime totals of one mint moved by a trading account, in base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintLedger {
+    pub mint: Pubkey,
+
+    /// Deposited by the owner
+    pub deposited: u128,
+
+    /// Withdrawn by the owner
+    pub withdrawn: u128,
+
+    /// Sent into pools by swaps, fees included
+    pub spent: u128,
+
+    /// Received from pools by swaps
+    pub received: u128,
+
+    /// Trade fees paid, already counted in `spent`; estimated like the
+    /// `CandleExecuted` fees, so only `spent` is exact
+    pub estimated_fees: u128,
+}
+
+impl MintLedger {
+    /// Size of one serialized ledger entry
+    /// 32 (mint) + 16 * 5 (totals)
+    pub const LEN: usize = 32 + 16 * 5;
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.deposited = accumulate(self.deposited, amount)?;
+        Ok(())
+    }
+
+    pub fn record_withdraw(&mut self, amount: u64) -> Result<()> {
+        self.withdrawn = accumulate(self.withdrawn, amount)?;
+        Ok(())
+    }
+
+    pub fn record_swap(&mut self, spent: u64, received: u64, estimated_fees: u64) -> Result<()> {
+        self.spent = accumulate(self.spent, spent)?;
+        self.received = accumulate(self.received, received)?;
+        self.estimated_fees = accumulate(self.estimated_fees, estimated_fees)?;
+        Ok(())
+    }
+}
+
+/// A deposit or swap moved `mint`, but the ledger was full and could not
+/// track it
+#[event]
+pub struct LedgerMintSkipped {
+    pub trading_account: Pubkey,
+    pub mint: Pubkey,
+}
+
+fn accumulate(total: u128, amount: u64) -> Result<u128> {
+    total
+        .checked_add(u128::from(amount))
+        .ok_or(error!(ErrorCode::Overflow))
 }
 
 impl TradingAccount {
-    /// Size calculation for account allocation
+    /// Layout written by this program version; bumped whenever the layout changes
+    ///
+    /// 1 is the first versioned layout. The legacy layout before it has no
+    /// version field and is recognised by its size, see `LEGACY_LEN`; it
+    /// counts as version 0.
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Size of the original, unversioned layout
     /// 8 (discriminator) + 32 (owner) + 1 (bump)
-    pub const LEN: usize = 8 + 32 + 1;
+    pub const LEGACY_LEN: usize = 8 + 32 + 1;
+
+    /// Offset of `version` in the serialized account
+    /// 8 (discriminator) + 32 (owner) + 1 (bump)
+    pub const VERSION_OFFSET: usize = 8 + 32 + 1;
+
+    /// Maximum number of operators per trading account
+    pub const MAX_OPERATORS: usize = 16;
+
+    /// Maximum number of mints tracked in the ledger
+    pub const MAX_LEDGER_MINTS: usize = 16;
+
+    /// Size calculation for account allocation (no operators, empty ledger)
+    /// 8 (discriminator) + 32 (owner) + 1 (bump) + 1 (version) + 2 (id)
+    /// + PnlPosition::LEN (pnl) + 1 (ledger_rebuild_pending) + 23 (reserved)
+    /// + 4 (operators length) + 4 (ledger length)
+    pub const LEN: usize = 8 + 32 + 1 + 1 + 2 + PnlPosition::LEN + 1 + 23 + 4 + 4;
+
+    /// PDA seed for sub-account `id`
+    ///
+    /// Account 0 uses an empty seed so it keeps the original
+    /// `["trading_account", owner]` address.
+    pub fn id_seed(id: u16) -> Vec<u8> {
+        if id == 0 {
+            Vec::new()
+        } else {
+            id.to_le_bytes().to_vec()
+        }
+    }
+
+    /// Account size holding `operators` operators and a full ledger
+    ///
+    /// Room for `MAX_LEDGER_MINTS` ledger entries is allocated up front and
+    /// paid by the owner, so swaps signed by an operator never have to grow
+    /// the account.
+    pub fn space(operators: usize) -> usize {
+        Self::LEN + Operator::LEN * operators + MintLedger::LEN * Self::MAX_LEDGER_MINTS
+    }
+
+    /// Ledger entry of `mint`, added empty on first use
+    ///
+    /// The account always has room for it, see `space`. Once the ledger holds
+    /// `MAX_LEDGER_MINTS` mints no other mint is added and this returns `None`:
+    /// deposits and swaps of that mint still go through, unrecorded, and emit
+    /// `LedgerMintSkipped` instead.
+    pub fn ledger_mut(&mut self, mint: Pubkey) -> Option<&mut MintLedger> {
+        let index = match self.ledger.iter().position(|entry| entry.mint == mint) {
+            Some(index) => index,
+            None if self.ledger.len() < Self::MAX_LEDGER_MINTS => {
+                self.ledger.push(MintLedger {
+                    mint,
+                    ..MintLedger::default()
+                });
+                self.ledger.len() - 1
+            }
+            None => return None,
+        };
+        Some(&mut self.ledger[index])
+    }
+
+    /// Ledger entry of `mint` if the ledger already tracks it
+    ///
+    /// Never adds an entry, so callers that must not fail on bookkeeping
+    /// (withdrawals) need no free ledger slot.
+    pub fn tracked_ledger_mut(&mut self, mint: &Pubkey) -> Option<&mut MintLedger> {
+        self.ledger.iter_mut().find(|entry| entry.mint == *mint)
+    }
+
+    pub fn find_operator(&self, key: &Pubkey) -> Option<usize> {
+        self.operators
+            .iter()
+            .position(|operator| operator.key == *key)
+    }
+
+    /// Whether `key` may swap: the owner, or an operator that has not expired
+    pub fn can_trade(&self, key: &Pubkey, now: i64) -> bool {
+        *key == self.owner
+            || self
+                .operators
+                .iter()
+                .any(|operator| operator.key == *key && operator.is_active(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trading_account() -> TradingAccount {
+        TradingAccount {
+            owner: Pubkey::new_unique(),
+            bump: 255,
+            version: TradingAccount::CURRENT_VERSION,
+            id: 0,
+            pnl: PnlPosition::default(),
+            ledger_rebuild_pending: false,
+            reserved: [0; 23],
+            operators: Vec::new(),
+            ledger: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ledger_mut_adds_each_mint_once() {
+        let mut account = trading_account();
+        let mint = Pubkey::new_unique();
+
+        account.ledger_mut(mint).unwrap().record_deposit(5).unwrap();
+        account.ledger_mut(mint).unwrap().record_deposit(7).unwrap();
+
+        assert_eq!(account.ledger.len(), 1);
+        assert_eq!(account.ledger[0].deposited, 12);
+    }
+
+    #[test]
+    fn full_ledger_keeps_recording_tracked_mints_only() {
+        let mut account = trading_account();
+        let mints: Vec<Pubkey> = (0..TradingAccount::MAX_LEDGER_MINTS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        for mint in &mints {
+            assert!(account.ledger_mut(*mint).is_some());
+        }
+
+        assert!(account.ledger_mut(Pubkey::new_unique()).is_none());
+        assert_eq!(account.ledger.len(), TradingAccount::MAX_LEDGER_MINTS);
+
+        account
+            .ledger_mut(mints[0])
+            .unwrap()
+            .record_deposit(1)
+            .unwrap();
+        assert_eq!(account.ledger[0].deposited, 1);
+    }
+
+    #[test]
+    fn full_ledger_fits_the_allocated_space() {
+        let mut account = trading_account();
+        for _ in 0..TradingAccount::MAX_LEDGER_MINTS {
+            account.ledger_mut(Pubkey::new_unique());
+        }
+
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), TradingAccount::space(0));
+    }
 }
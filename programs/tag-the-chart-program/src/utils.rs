@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+/// Read the balance of an SPL Token or Token-2022 account (0 if not created yet)
+pub fn token_balance(account: &AccountInfo) -> Result<u64> {
+    if account.data_is_empty() {
+        return Ok(0);
+    }
+    let data = account.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &data[..])?;
+    Ok(token_account.amount)
+}
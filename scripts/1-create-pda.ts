@@ -56,7 +56,7 @@ async function main() {
   // Create PDA
   try {
     const tx = await program.methods
-      .initialize()
+      .initialize(0)
       .accounts({
         user: user.publicKey,
       })
@@ -6,19 +6,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { TagTheChartProgram } from "../target/types/tag_the_chart_program";
-import {
-  PublicKey,
-  Keypair,
-  SystemProgram,
-  Transaction,
-  LAMPORTS_PER_SOL,
-} from "@solana/web3.js";
-import {
-  getAssociatedTokenAddressSync,
-  createSyncNativeInstruction,
-  NATIVE_MINT,
-  TOKEN_PROGRAM_ID,
-} from "@solana/spl-token";
+import { PublicKey, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync, NATIVE_MINT } from "@solana/spl-token";
 import * as dotenv from "dotenv";
 
 dotenv.config();
@@ -62,18 +51,12 @@ async function main() {
 
   console.log("📝 PDA WSOL ATA:", pdaWsolAta.toString());
 
-  // Wrap SOL directly to PDA's WSOL ATA
+  // Wrap SOL directly to PDA's WSOL ATA (created if missing)
   console.log("\n⏳ Wrapping SOL directly to PDA...");
-  const wrapTx = new Transaction().add(
-    SystemProgram.transfer({
-      fromPubkey: user.publicKey,
-      toPubkey: pdaWsolAta,
-      lamports: SOL_AMOUNT * LAMPORTS_PER_SOL,
-    }),
-    createSyncNativeInstruction(pdaWsolAta)
-  );
-
-  const wrapSig = await provider.sendAndConfirm(wrapTx);
+  const wrapSig = await program.methods
+    .depositSol(0, new BN(SOL_AMOUNT * LAMPORTS_PER_SOL))
+    .accounts({ user: user.publicKey })
+    .rpc();
   console.log("✅ SOL wrapped directly to PDA!");
   console.log(`https://explorer.solana.com/tx/${wrapSig}?cluster=devnet`);
 
@@ -84,18 +84,9 @@ async function main() {
   console.log("  Low (+0.01%):", targetPrices[1].toString());
   console.log("  Close (+0.02%):", targetPrices[2].toString());
 
-  // Determine which is WSOL and which is the other token
-  const WSOL_MINT = new PublicKey(
-    "So11111111111111111111111111111111111111112"
-  );
-  const isTokenAWsol = pool.tokenMintA.equals(WSOL_MINT);
-
-  const tradingAccountWsol = isTokenAWsol ? pdaAtaA : pdaAtaB;
-  const tradingAccountToken = isTokenAWsol ? pdaAtaB : pdaAtaA;
-
   console.log("\n📝 PDA Token Accounts:");
-  console.log("  WSOL:", tradingAccountWsol.toString());
-  console.log("  Token:", tradingAccountToken.toString());
+  console.log("  A:", pdaAtaA.toString());
+  console.log("  B:", pdaAtaB.toString());
 
   // Execute swap
   console.log("\n⏳ Executing swap...");
@@ -104,16 +95,26 @@ async function main() {
     const maxInputs = [new BN(0), new BN(0), new BN(0)];
     const minOutputs = [new BN(0), new BN(0), new BN(0)];
 
+    // Trading account 0, the one at the original PDA address
     const sig = await program.methods
-      .swapToPrices(targetPrices, maxInputs, minOutputs)
+      .swapToPrices(
+        0,
+        targetPrices,
+        maxInputs,
+        minOutputs,
+        [],
+        [],
+        null,
+        null
+      )
       .accounts({
         user: signer.publicKey,
         tradingAccount: pda,
         raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
         ammConfig: pool.ammConfig,
         poolState: POOL_ADDRESS,
-        tradingAccountToken: tradingAccountToken,
-        tradingAccountWsol: tradingAccountWsol,
+        tradingAccountTokenA: pdaAtaA,
+        tradingAccountTokenB: pdaAtaB,
         tokenVaultA: pool.tokenVaultA,
         tokenVaultB: pool.tokenVaultB,
         tokenMintA: pool.tokenMintA,
@@ -32,6 +32,7 @@ import {
   NATIVE_MINT,
   getAccount,
 } from "@solana/spl-token";
+import { fetchPoolAccounts } from "../tests/utils/pool-helper";
 import * as dotenv from "dotenv";
 
 dotenv.config();
@@ -56,7 +57,6 @@ const WSOL_MINT = NATIVE_MINT; // So11111111111111111111111111111111111111112
 // Swap configuration
 const WSOL_AMOUNT_TO_WRAP = 0.01; // 0.01 SOL
 const TOKEN_AMOUNT_TO_DEPOSIT = 100; // Adjust based on your token mint decimals
-const SLIPPAGE_BPS = 100; // 1% slippage
 
 async function main() {
   const signer = process.env.TOKEN_HOLDER_SECRET_KEY;
@@ -140,12 +140,8 @@ async function main() {
   if (!pdaExists) {
     try {
       const tx = await program.methods
-        .initialize()
-        .accounts({
-          user: user.publicKey,
-          tradingAccount: tradingAccount,
-          systemProgram: SystemProgram.programId,
-        })
+        .initialize(0)
+        .accounts({ user: user.publicKey })
         .rpc();
       console.log("  ✅ PDA initialized. Signature:", tx);
     } catch (error) {
@@ -214,16 +210,12 @@ async function main() {
   const wsolDepositAmount = new BN(WSOL_AMOUNT_TO_WRAP * LAMPORTS_PER_SOL);
   try {
     const tx = await program.methods
-      .deposit(wsolDepositAmount)
+      .deposit(0, wsolDepositAmount)
       .accounts({
-        user: user.publicKey,
-        tradingAccount: tradingAccount,
         userTokenAccount: userWsolAta,
         pdaTokenAccount: pdaWsolAta,
-        tokenMint: WSOL_MINT,
+        mint: WSOL_MINT,
         tokenProgram: TOKEN_PROGRAM_ID,
-        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
-        systemProgram: SystemProgram.programId,
       })
       .rpc();
     console.log("  ✅ WSOL deposited. Signature:", tx);
@@ -239,16 +231,12 @@ async function main() {
     );
     try {
       const tx = await program.methods
-        .deposit(tokenDepositAmount)
+        .deposit(0, tokenDepositAmount)
         .accounts({
-          user: user.publicKey,
-          tradingAccount: tradingAccount,
           userTokenAccount: userTokenAta,
           pdaTokenAccount: pdaTokenAta,
-          tokenMint: tokenMint,
+          mint: tokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
         })
         .rpc();
       console.log("  ✅ Token deposited. Signature:", tx);
@@ -262,28 +250,24 @@ async function main() {
 
   // 11. Fetch pool accounts for swap
   console.log("\n⏳ Fetching pool accounts...");
-  const poolStateAccount = await connection.getAccountInfo(POOL_ADDRESS);
-  if (!poolStateAccount) {
-    throw new Error("❌ Pool state account not found");
-  }
+  const pool = await fetchPoolAccounts(POOL_ADDRESS);
 
-  // Parse pool state to get required accounts (simplified)
-  const ammConfigAddress = new PublicKey(poolData.slice(41, 73));
-  const tokenVault0 = new PublicKey(poolData.slice(137, 169));
-  const tokenVault1 = new PublicKey(poolData.slice(169, 201));
-  const observationKey = new PublicKey(poolData.slice(201, 233));
+  console.log("  AMM Config:", pool.ammConfig.toString());
+  console.log("  Token Vault A:", pool.tokenVaultA.toString());
+  console.log("  Token Vault B:", pool.tokenVaultB.toString());
+  console.log("  Observation:", pool.observationState.toString());
 
-  console.log("  AMM Config:", ammConfigAddress.toString());
-  console.log("  Token Vault 0:", tokenVault0.toString());
-  console.log("  Token Vault 1:", tokenVault1.toString());
-  console.log("  Observation:", observationKey.toString());
+  // The swap takes the PDA's token accounts in pool mint order (A/B)
+  const [pdaAtaA, pdaAtaB] = isWsolMint0
+    ? [pdaWsolAta, pdaTokenAta]
+    : [pdaTokenAta, pdaWsolAta];
 
   // 12. Execute swap
   console.log("\n⏳ Executing swap to prices...");
 
   // Example target sqrt prices (you should calculate these based on desired OHLC)
   // These are placeholder values - replace with actual calculated sqrt prices
-  const currentPrice = new BN(poolData.slice(253, 269)); // sqrt_price_x64 at offset 253
+  const currentPrice = new BN(pool.currentSqrtPrice.toString());
   console.log("  Current sqrt price (X64):", currentPrice.toString());
 
   // Create 3 target prices (example: slightly different from current)
@@ -300,25 +284,37 @@ async function main() {
   );
 
   try {
+    // Trading account 0, no input or output limits (0), default price
+    // tolerance and exact-input steps ([]), no open price guard or spend
+    // caps (null)
+    const noLimits = targetPrices.map(() => new BN(0));
     const swapTx = await program.methods
-      .swapToPrices(targetPrices, SLIPPAGE_BPS)
+      .swapToPrices(0, targetPrices, noLimits, noLimits, [], [], null, null)
       .accounts({
         user: user.publicKey,
         tradingAccount: tradingAccount,
+        raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
+        ammConfig: pool.ammConfig,
         poolState: POOL_ADDRESS,
-        ammConfig: ammConfigAddress,
-        inputTokenAccount: pdaWsolAta,
-        outputTokenAccount: pdaTokenAta,
-        inputVault: tokenVault0,
-        outputVault: tokenVault1,
-        inputTokenMint: WSOL_MINT,
-        outputTokenMint: tokenMint,
-        observationState: observationKey,
-        ammProgram: RAYDIUM_CLMM_PROGRAM_ID,
+        tradingAccountTokenA: pdaAtaA,
+        tradingAccountTokenB: pdaAtaB,
+        tokenVaultA: pool.tokenVaultA,
+        tokenVaultB: pool.tokenVaultB,
+        tokenMintA: pool.tokenMintA,
+        tokenMintB: pool.tokenMintB,
+        observationState: pool.observationState,
         tokenProgram: TOKEN_PROGRAM_ID,
         tokenProgram2022: TOKEN_2022_PROGRAM_ID,
         memoProgram: MEMO_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(
+        pool.tickArrays.map((pubkey) => ({
+          pubkey,
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
       ])
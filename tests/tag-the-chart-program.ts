@@ -13,9 +13,17 @@ import {
   createAssociatedTokenAccountInstruction,
   createSyncNativeInstruction,
   createTransferInstruction,
+  createAccount,
+  createMint,
 } from "@solana/spl-token";
-import { fetchPoolAccounts } from "./utils/pool-helper";
+import {
+  fetchLocalPoolState,
+  fetchPoolAccounts,
+  PoolAccounts,
+} from "./utils/pool-helper";
+import { tradingAccountPda } from "./utils/pda";
 import { expect } from "chai";
+import * as fs from "fs";
 
 import * as dotenv from "dotenv";
 
@@ -39,11 +47,25 @@ const MEMO_PROGRAM_ID = new PublicKey(
   "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
 );
 
+// Associated Token Account program
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+  "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+);
+
 // Raydium CLMM pool address
 const POOL_ADDRESS = new PublicKey(
   "6A1PJ4HnmhX7KHHrBS9FvSLQoU7hzauB8hvFQtvrfGUi"
 );
 
+// BPF upgradeable loader, owner of the program data account
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+// Size of a trading account in the current layout, with no operators and room
+// for a full ledger of 16 mints (TradingAccount::space(0))
+const TRADING_ACCOUNT_LEN = 180 + 16 * 112;
+
 // Wrapped SOL mint
 const WSOL_MINT = new PublicKey("So11111111111111111111111111111111111111112");
 
@@ -79,6 +101,105 @@ describe("tag-the-chart-program", () => {
   let userTokenAta: PublicKey;
   let pdaWsolAta: PublicKey;
   let pdaTokenAta: PublicKey;
+  // Same accounts, in pool mint order (A/B)
+  let userAtaA: PublicKey;
+  let userAtaB: PublicKey;
+  let pdaAtaA: PublicKey;
+  let pdaAtaB: PublicKey;
+  // Can pause swaps alongside the admin
+  const guardian = Keypair.generate();
+
+  // Accounts of the simple (wallet) swap instructions on `pool`
+  const simpleSwapAccounts = (pool: PoolAccounts, overrides = {}) => ({
+    wallet: user.publicKey,
+    tokenAccountA: userAtaA,
+    tokenAccountB: userAtaB,
+    raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
+    ammConfig: pool.ammConfig,
+    poolState: pool.poolAddress,
+    tokenVaultA: pool.tokenVaultA,
+    tokenVaultB: pool.tokenVaultB,
+    tokenMintA: pool.tokenMintA,
+    tokenMintB: pool.tokenMintB,
+    observationState: pool.observationState,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+    memoProgram: MEMO_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    ...overrides,
+  });
+
+  // Accounts of the PDA swap instructions on `pool`, signed by the owner
+  const pdaSwapAccounts = (pool: PoolAccounts, overrides = {}) => ({
+    user: user.publicKey,
+    tradingAccount,
+    raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
+    ammConfig: pool.ammConfig,
+    poolState: pool.poolAddress,
+    tradingAccountTokenA: pdaAtaA,
+    tradingAccountTokenB: pdaAtaB,
+    tokenVaultA: pool.tokenVaultA,
+    tokenVaultB: pool.tokenVaultB,
+    tokenMintA: pool.tokenMintA,
+    tokenMintB: pool.tokenMintB,
+    observationState: pool.observationState,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+    memoProgram: MEMO_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    ...overrides,
+  });
+
+  // Tick arrays of `pool`, writable, in the order the swaps expect them
+  const tickArrayAccounts = (pool: PoolAccounts) =>
+    pool.tickArrays.map((tickArray) => ({
+      pubkey: tickArray,
+      isWritable: true,
+      isSigner: false,
+    }));
+
+  // Simple swap on the test pool with some of its accounts replaced, expected
+  // to fail with `code`
+  const expectSimpleSwapError = async (overrides: object, code: string) => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool, overrides))
+        .rpc();
+      expect.fail(`swap should fail with ${code}`);
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal(code);
+    }
+  };
+
+  // What the wallet can spend of `mint`: half its SOL for WSOL, which the
+  // simple swaps wrap on the fly, otherwise its token balance
+  const walletHolding = async (mint: PublicKey, ata: PublicKey) => {
+    if (mint.equals(WSOL_MINT)) {
+      const lamports = await provider.connection.getBalance(user.publicKey);
+      return new BN(lamports / 2);
+    }
+    const balance = await provider.connection.getTokenAccountBalance(ata);
+    return new BN(balance.value.amount);
+  };
+
+  // WSOL entry of the main trading account's ledger
+  const wsolLedger = async () => {
+    const account = await program.account.tradingAccount.fetch(tradingAccount);
+    return account.ledger.find((entry) => entry.mint.equals(WSOL_MINT));
+  };
 
   before("Setup test environment", async () => {
     console.log("\n🔧 Setting up test environment...\n");
@@ -94,14 +215,34 @@ describe("tag-the-chart-program", () => {
     console.log("  ✅ Airdropped 5 SOL for fees");
 
     // Initialize trading account PDA
-    [tradingAccount] = PublicKey.findProgramAddressSync(
-      [Buffer.from("trading_account"), user.publicKey.toBuffer()],
-      program.programId
-    );
+    [tradingAccount] = tradingAccountPda(program.programId, user.publicKey, 0);
 
-    await program.methods.initialize().accounts({ user: user.publicKey }).rpc();
+    await program.methods.initialize(0).accounts({ user: user.publicKey }).rpc();
     console.log("  ✅ Initialized trading account");
 
+    // Only the upgrade authority (the deploy wallet) can create the config;
+    // make the test user its admin so the tests can manage the allowlist
+    const deployer = (anchor.AnchorProvider.env().wallet as anchor.Wallet)
+      .payer;
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    await program.methods
+      .initializeConfig(user.publicKey, guardian.publicKey)
+      .accounts({
+        authority: deployer.publicKey,
+        //@ts-ignore
+        programData,
+      })
+      .signers([deployer])
+      .rpc();
+    await program.methods
+      .addPool()
+      .accounts({ admin: user.publicKey, poolState: POOL_ADDRESS })
+      .rpc();
+    console.log("  ✅ Initialized program config and allowed the pool");
+
     // Fetch pool to determine token mints
     const pool = await fetchPoolAccounts(POOL_ADDRESS);
     const isMintAWSOL = pool.tokenMintA.equals(WSOL_MINT);
@@ -125,6 +266,12 @@ describe("tag-the-chart-program", () => {
       tradingAccount,
       true
     );
+    [userAtaA, userAtaB] = isMintAWSOL
+      ? [userWsolAta, userTokenAta]
+      : [userTokenAta, userWsolAta];
+    [pdaAtaA, pdaAtaB] = isMintAWSOL
+      ? [pdaWsolAta, pdaTokenAta]
+      : [pdaTokenAta, pdaWsolAta];
 
     // Create all ATAs if needed
     const ataInstructions = [];
@@ -201,13 +348,12 @@ describe("tag-the-chart-program", () => {
     await provider.sendAndConfirm(wrapTx);
   });
 
-  it.skip("Deposit both pool tokens (WSOL + Token)", async () => {
+  it("Deposit both pool tokens (WSOL + Token)", async () => {
     // Deposit 0.005 WSOL to PDA (half of available 0.01)
 
     const wsolDepositAmount = new BN(0.005 * LAMPORTS_PER_SOL);
     await program.methods
-      //@ts-expect-error
-      .deposit(wsolDepositAmount)
+      .deposit(0, wsolDepositAmount)
       .accounts({
         userTokenAccount: userWsolAta,
         pdaTokenAccount: pdaWsolAta,
@@ -219,8 +365,7 @@ describe("tag-the-chart-program", () => {
     // Deposit 50 tokens to PDA (half of available 100)
     const tokenDepositAmount = new BN(50_000_000_000); // 50 tokens
     await program.methods
-      //@ts-expect-error
-      .deposit(tokenDepositAmount)
+      .deposit(0, tokenDepositAmount)
       .accounts({
         userTokenAccount: userTokenAta,
         pdaTokenAccount: pdaTokenAta,
@@ -241,7 +386,7 @@ describe("tag-the-chart-program", () => {
     expect(pdaTokenBalance.value.uiAmount).to.equal(50);
   });
 
-  it.skip("Withdraw both pool tokens (WSOL + Token)", async () => {
+  it("Withdraw both pool tokens (WSOL + Token)", async () => {
     // Get balances before withdrawal
     const userWsolBefore = await provider.connection.getTokenAccountBalance(
       userWsolAta
@@ -259,8 +404,7 @@ describe("tag-the-chart-program", () => {
     // Withdraw 0.002 WSOL from PDA
     const wsolWithdrawAmount = new BN(0.002 * LAMPORTS_PER_SOL);
     await program.methods
-      //@ts-expect-error
-      .withdraw(wsolWithdrawAmount)
+      .withdraw(0, wsolWithdrawAmount)
       .accounts({
         userTokenAccount: userWsolAta,
         pdaTokenAccount: pdaWsolAta,
@@ -272,8 +416,7 @@ describe("tag-the-chart-program", () => {
     // Withdraw 20 tokens from PDA
     const tokenWithdrawAmount = new BN(20_000_000_000); // 20 tokens
     await program.methods
-      //@ts-expect-error
-      .withdraw(tokenWithdrawAmount)
+      .withdraw(0, tokenWithdrawAmount)
       .accounts({
         userTokenAccount: userTokenAta,
         pdaTokenAccount: pdaTokenAta,
@@ -313,12 +456,413 @@ describe("tag-the-chart-program", () => {
     );
   });
 
-  it.skip("Swap to target prices with forked pool", async () => {
+  it("Deposits native SOL as WSOL in one instruction", async () => {
+    const before = await provider.connection.getTokenAccountBalance(pdaWsolAta);
+    const ledgerBefore = await wsolLedger();
+
+    const amount = new BN(0.005 * LAMPORTS_PER_SOL);
+    await program.methods
+      .depositSol(0, amount)
+      .accounts({ user: user.publicKey })
+      .rpc();
+
+    const after = await provider.connection.getTokenAccountBalance(pdaWsolAta);
+    expect(
+      new BN(after.value.amount).sub(new BN(before.value.amount)).toString()
+    ).to.equal(amount.toString());
+    expect(
+      (await wsolLedger()).deposited.sub(ledgerBefore.deposited).toString()
+    ).to.equal(amount.toString());
+  });
+
+  it("Initializes a separate trading sub-account", async () => {
+    const id = 1;
+    const [subAccount] = tradingAccountPda(
+      program.programId,
+      user.publicKey,
+      id
+    );
+
+    await program.methods.initialize(id).accounts({ user: user.publicKey }).rpc();
+
+    const account = await program.account.tradingAccount.fetch(subAccount);
+    expect(account.owner.toBase58()).to.equal(user.publicKey.toBase58());
+    expect(account.id).to.equal(id);
+    expect(subAccount.equals(tradingAccount)).to.be.false;
+  });
+
+  it("Refuses to migrate an account that already uses the current layout", async () => {
+    const account = await program.account.tradingAccount.fetch(tradingAccount);
+    expect(account.version).to.equal(1);
+
+    try {
+      await program.methods
+        .migrateTradingAccount()
+        .accounts({ user: user.publicKey })
+        .rpc();
+      expect.fail("migrating a current account should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("AlreadyMigrated");
+    }
+  });
+
+  it("Migrates a trading account from the legacy layout", async () => {
+    // Loaded into the validator from tests/fixtures, see Anchor.toml
+    const legacyOwner = Keypair.fromSecretKey(
+      Uint8Array.from(
+        JSON.parse(fs.readFileSync("tests/fixtures/legacy-owner.json", "utf8"))
+      )
+    );
+    const [legacyAccount, bump] = tradingAccountPda(
+      program.programId,
+      legacyOwner.publicKey,
+      0
+    );
+
+    const before = await provider.connection.getAccountInfo(legacyAccount);
+    expect(before.data.length).to.equal(8 + 32 + 1);
+
+    // The owner pays the rent for the extra space
+    const airdropSig = await provider.connection.requestAirdrop(
+      legacyOwner.publicKey,
+      LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    await program.methods
+      .migrateTradingAccount()
+      .accounts({ user: legacyOwner.publicKey })
+      .signers([legacyOwner])
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(legacyAccount);
+    expect(after.data.length).to.equal(TRADING_ACCOUNT_LEN);
+
+    const account = await program.account.tradingAccount.fetch(legacyAccount);
+    expect(account.owner.toBase58()).to.equal(
+      legacyOwner.publicKey.toBase58()
+    );
+    expect(account.bump).to.equal(bump);
+    expect(account.version).to.equal(1);
+    expect(account.id).to.equal(0);
+    expect(account.operators).to.be.empty;
+    expect(account.ledger).to.be.empty;
+    expect(account.ledgerRebuildPending).to.be.true;
+  });
+
+  it("Closes a migrated account only once its ledger is rebuilt", async () => {
+    const legacyOwner = Keypair.fromSecretKey(
+      Uint8Array.from(
+        JSON.parse(fs.readFileSync("tests/fixtures/legacy-owner.json", "utf8"))
+      )
+    );
+    const [legacyAccount] = tradingAccountPda(
+      program.programId,
+      legacyOwner.publicKey,
+      0
+    );
+    // Held before migration, so the ledger knows nothing about it
+    const legacyWsolAta = getAssociatedTokenAddressSync(
+      WSOL_MINT,
+      legacyAccount,
+      true
+    );
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          user.publicKey,
+          legacyWsolAta,
+          legacyAccount,
+          WSOL_MINT
+        )
+      )
+    );
+    const wsolTriple = [
+      { pubkey: legacyWsolAta, isSigner: false, isWritable: true },
+      { pubkey: WSOL_MINT, isSigner: false, isWritable: false },
+      { pubkey: legacyOwner.publicKey, isSigner: false, isWritable: true },
+    ];
+
+    try {
+      await program.methods
+        .closeTradingAccount(0)
+        .accounts({ user: legacyOwner.publicKey })
+        .remainingAccounts(wsolTriple)
+        .signers([legacyOwner])
+        .rpc();
+      expect.fail("closing before the ledger is rebuilt should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("LedgerRebuildPending");
+    }
+
+    await program.methods
+      .rebuildLedger(0)
+      .accounts({ user: legacyOwner.publicKey })
+      .remainingAccounts([
+        { pubkey: legacyWsolAta, isSigner: false, isWritable: false },
+      ])
+      .signers([legacyOwner])
+      .rpc();
+
+    const account = await program.account.tradingAccount.fetch(legacyAccount);
+    expect(account.ledgerRebuildPending).to.be.false;
+    expect(account.ledger.map((entry) => entry.mint.toBase58())).to.deep.equal(
+      [WSOL_MINT.toBase58()]
+    );
+
+    await program.methods
+      .closeTradingAccount(0)
+      .accounts({ user: legacyOwner.publicKey })
+      .remainingAccounts(wsolTriple)
+      .signers([legacyOwner])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(legacyAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(legacyWsolAta)).to.be.null;
+  });
+
+  it("Closes a funded sub-account and returns everything to the owner", async () => {
+    const id = 2;
+    const [subAccount] = tradingAccountPda(
+      program.programId,
+      user.publicKey,
+      id
+    );
+    const subWsolAta = getAssociatedTokenAddressSync(
+      WSOL_MINT,
+      subAccount,
+      true
+    );
+
+    await program.methods.initialize(id).accounts({ user: user.publicKey }).rpc();
+    await program.methods
+      .depositSol(id, new BN(0.01 * LAMPORTS_PER_SOL))
+      .accounts({ user: user.publicKey })
+      .rpc();
+
+    // The ledger holds WSOL, so its account has to be closed along the way
+    try {
+      await program.methods
+        .closeTradingAccount(id)
+        .accounts({ user: user.publicKey })
+        .rpc();
+      expect.fail("close leaving the WSOL account open should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("MissingTokenAccount");
+    }
+
+    // Each token account can only be closed once
+    try {
+      await program.methods
+        .closeTradingAccount(id)
+        .accounts({ user: user.publicKey })
+        .remainingAccounts([
+          { pubkey: subWsolAta, isSigner: false, isWritable: true },
+          { pubkey: WSOL_MINT, isSigner: false, isWritable: false },
+          { pubkey: user.publicKey, isSigner: false, isWritable: true },
+          { pubkey: subWsolAta, isSigner: false, isWritable: true },
+          { pubkey: WSOL_MINT, isSigner: false, isWritable: false },
+          { pubkey: user.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+      expect.fail("close passing a token account twice should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("DuplicateTokenAccount");
+    }
+
+    // WSOL is unwrapped to the wallet, never to a token account
+    try {
+      await program.methods
+        .closeTradingAccount(id)
+        .accounts({ user: user.publicKey })
+        .remainingAccounts([
+          { pubkey: subWsolAta, isSigner: false, isWritable: true },
+          { pubkey: WSOL_MINT, isSigner: false, isWritable: false },
+          { pubkey: userWsolAta, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+      expect.fail("close to a WSOL token account should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidWsolDestination");
+    }
+
+    const lamportsBefore = await provider.connection.getBalance(user.publicKey);
+    const reclaimable =
+      (await provider.connection.getBalance(subAccount)) +
+      (await provider.connection.getBalance(subWsolAta));
+
+    await program.methods
+      .closeTradingAccount(id)
+      .accounts({ user: user.publicKey })
+      .remainingAccounts([
+        { pubkey: subWsolAta, isSigner: false, isWritable: true },
+        { pubkey: WSOL_MINT, isSigner: false, isWritable: false },
+        { pubkey: user.publicKey, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(subAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(subWsolAta)).to.be.null;
+    // Everything but the transaction fee comes back to the owner
+    const lamportsAfter = await provider.connection.getBalance(user.publicKey);
+    expect(lamportsAfter - lamportsBefore).to.be.greaterThan(
+      reclaimable - 10_000
+    );
+  });
+
+  it("Closes a sub-account whose tokens sit outside its ATA", async () => {
+    const id = 4;
+    const [subAccount] = tradingAccountPda(
+      program.programId,
+      user.publicKey,
+      id
+    );
+
+    await program.methods.initialize(id).accounts({ user: user.publicKey }).rpc();
+
+    // Deposits accept any token account of the PDA, not only its ATA
+    const subTokenAccount = await createAccount(
+      provider.connection,
+      user,
+      tokenMint,
+      subAccount,
+      Keypair.generate()
+    );
+    const amount = new BN(1_000_000);
+    await program.methods
+      .deposit(id, amount)
+      .accounts({
+        userTokenAccount: userTokenAta,
+        pdaTokenAccount: subTokenAccount,
+        mint: tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const subTokenAta = getAssociatedTokenAddressSync(
+      tokenMint,
+      subAccount,
+      true
+    );
+    const subTokenTriple = [
+      { pubkey: subTokenAccount, isSigner: false, isWritable: true },
+      { pubkey: tokenMint, isSigner: false, isWritable: false },
+      { pubkey: userTokenAta, isSigner: false, isWritable: true },
+    ];
+
+    // The ATA of every ledger mint is required, even when it never existed
+    try {
+      await program.methods
+        .closeTradingAccount(id)
+        .accounts({ user: user.publicKey })
+        .remainingAccounts(subTokenTriple)
+        .rpc();
+      expect.fail("close without the mint's ATA should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("MissingTokenAccount");
+    }
+
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          user.publicKey,
+          subTokenAta,
+          subAccount,
+          tokenMint
+        )
+      )
+    );
+
+    const userTokenBefore = await provider.connection.getTokenAccountBalance(
+      userTokenAta
+    );
+
+    // The empty ATA and the account holding the balance are both closed
+    await program.methods
+      .closeTradingAccount(id)
+      .accounts({ user: user.publicKey })
+      .remainingAccounts([
+        { pubkey: subTokenAta, isSigner: false, isWritable: true },
+        { pubkey: tokenMint, isSigner: false, isWritable: false },
+        { pubkey: userTokenAta, isSigner: false, isWritable: true },
+        ...subTokenTriple,
+      ])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(subTokenAta)).to.be.null;
+    expect(await provider.connection.getAccountInfo(subAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(subTokenAccount)).to.be
+      .null;
+    const userTokenAfter = await provider.connection.getTokenAccountBalance(
+      userTokenAta
+    );
+    expect(
+      new BN(userTokenAfter.value.amount)
+        .sub(new BN(userTokenBefore.value.amount))
+        .toString()
+    ).to.equal(amount.toString());
+  });
+
+  it("Withdraws a balance the ledger never tracked", async () => {
+    const id = 3;
+    const [subAccount] = tradingAccountPda(
+      program.programId,
+      user.publicKey,
+      id
+    );
+    const subTokenAta = getAssociatedTokenAddressSync(
+      tokenMint,
+      subAccount,
+      true
+    );
+
+    await program.methods.initialize(id).accounts({ user: user.publicKey }).rpc();
+
+    // Tokens sent straight to the PDA's token account bypass the ledger
+    const amount = 1_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          user.publicKey,
+          subTokenAta,
+          subAccount,
+          tokenMint
+        ),
+        createTransferInstruction(
+          userTokenAta,
+          subTokenAta,
+          user.publicKey,
+          amount
+        )
+      )
+    );
+
+    await program.methods
+      .withdraw(id, new BN(amount))
+      .accounts({
+        userTokenAccount: userTokenAta,
+        pdaTokenAccount: subTokenAta,
+        mint: tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const balance = await provider.connection.getTokenAccountBalance(
+      subTokenAta
+    );
+    expect(balance.value.amount).to.equal("0");
+    // Nothing is recorded and the account keeps its size
+    const account = await program.account.tradingAccount.fetch(subAccount);
+    expect(account.ledger).to.be.empty;
+    const info = await provider.connection.getAccountInfo(subAccount);
+    expect(info.data.length).to.equal(TRADING_ACCOUNT_LEN);
+  });
+
+  it("Swap to target prices with forked pool", async () => {
     const pool = await fetchPoolAccounts(POOL_ADDRESS);
 
     await program.methods
-      //@ts-expect-error
-      .deposit(new BN(0.01 * LAMPORTS_PER_SOL))
+      .deposit(0, new BN(0.01 * LAMPORTS_PER_SOL))
       .accounts({
         userTokenAccount: userWsolAta,
         pdaTokenAccount: pdaWsolAta,
@@ -328,8 +872,7 @@ describe("tag-the-chart-program", () => {
       .rpc();
 
     await program.methods
-      //@ts-expect-error
-      .deposit(new BN(50_000_000_000))
+      .deposit(0, new BN(50_000_000_000))
       .accounts({
         userTokenAccount: userTokenAta,
         pdaTokenAccount: pdaTokenAta,
@@ -358,42 +901,281 @@ describe("tag-the-chart-program", () => {
     const maxInputs = [new BN(0), new BN(0), new BN(0)];
     const minOutputs = [new BN(0), new BN(0), new BN(0)];
 
+    // Per-step price-reached tolerance: 0.1% for the swings, 0.05% for the close
+    const tolerancesBps = [10, 10, 5];
+
     const tx = await program.methods
-      //@ts-expect-error
-      .swapToPrices(targetSqrtPrices, maxInputs, minOutputs)
-      .accounts({
-        user: user.publicKey,
-        //@ts-ignore
-        raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
-        ammConfig: pool.ammConfig,
-        poolState: pool.poolAddress,
-        tradingAccountToken: pdaTokenAta,
-        tradingAccountWsol: pdaWsolAta,
-        tokenVaultA: pool.tokenVaultA,
-        tokenVaultB: pool.tokenVaultB,
-        tokenMintA: pool.tokenMintA,
-        tokenMintB: pool.tokenMintB,
-        observationState: pool.observationState,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
-        memoProgram: MEMO_PROGRAM_ID,
-        systemProgram: SystemProgram.programId,
-      })
-      .remainingAccounts(
-        pool.tickArrays.map((tickArray) => ({
-          pubkey: tickArray,
-          isWritable: true,
-          isSigner: false,
-        }))
+      .swapToPrices(
+        0,
+        targetSqrtPrices,
+        maxInputs,
+        minOutputs,
+        tolerancesBps,
+        [],
+        null,
+        null
       )
+      .accounts(pdaSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
       .preInstructions([computeBudgetIx])
       .rpc();
 
+    // Both sides of the pool show up in the ledger after a round trip
+    const account = await program.account.tradingAccount.fetch(tradingAccount);
+    for (const mint of [pool.tokenMintA, pool.tokenMintB]) {
+      const entry = account.ledger.find((e) => e.mint.equals(mint));
+      expect(entry.spent.gtn(0)).to.be.true;
+      expect(entry.received.gtn(0)).to.be.true;
+    }
+
     console.log("  ✅ Executed candle pattern swap");
     console.log("  Transaction signature:", tx);
   });
 
-  it.only("Swap to target prices (simple - no PDA)", async () => {
+  it("Tracks cost basis and realized PnL of the base token", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // Base is the non-WSOL token, valued in WSOL
+    await program.methods
+      .configurePnlPair(0, tokenMint, WSOL_MINT)
+      .accounts({ user: user.publicKey })
+      .rpc();
+
+    // Price is token A in token B: buying the base moves it up when the
+    // base is A, down otherwise
+    const currentPrice = pool.currentSqrtPrice;
+    const buyPrice = pool.tokenMintA.equals(tokenMint)
+      ? (currentPrice * 10005n) / 10000n
+      : (currentPrice * 9995n) / 10000n;
+
+    const swapTo = (target: bigint) =>
+      program.methods
+        .swapToPrices(
+          0,
+          [new BN(target.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          null,
+          null
+        )
+        .accounts(pdaSwapAccounts(pool))
+        .remainingAccounts(tickArrayAccounts(pool))
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ])
+        .rpc();
+
+    await swapTo(buyPrice);
+    let { pnl } = await program.account.tradingAccount.fetch(tradingAccount);
+    expect(pnl.position.gtn(0)).to.be.true;
+    expect(pnl.costBasis.gtn(0)).to.be.true;
+    const bought = pnl.position;
+
+    // Selling back through the same liquidity pays fees both ways
+    await swapTo(currentPrice);
+    ({ pnl } = await program.account.tradingAccount.fetch(tradingAccount));
+    expect(pnl.position.lt(bought)).to.be.true;
+    expect(pnl.realizedPnl.ltn(0)).to.be.true;
+  });
+
+  it("Rejects a PDA swap with swapped token accounts", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    const targetSqrtPrices = [new BN(pool.currentSqrtPrice.toString())];
+
+    try {
+      await program.methods
+        .swapToPrices(
+          0,
+          targetSqrtPrices,
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          null,
+          null
+        )
+        .accounts(
+          pdaSwapAccounts(pool, {
+            tradingAccountTokenA: pdaAtaB,
+            tradingAccountTokenB: pdaAtaA,
+          })
+        )
+        .rpc();
+      expect.fail("swap with swapped token accounts should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidTokenMint");
+    }
+  });
+
+  it("Rejects a PDA swap whose id does not match the trading account", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    try {
+      await program.methods
+        .swapToPrices(
+          1,
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          null,
+          null
+        )
+        .accounts(pdaSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap naming another sub-account should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+    }
+  });
+
+  it("Lets an operator swap but never withdraw", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const operator = Keypair.generate();
+
+    await program.methods
+      .addOperator(0, operator.publicKey, new BN(0))
+      .accounts({ user: user.publicKey })
+      .rpc();
+
+    const currentPrice = pool.currentSqrtPrice;
+    const operatorSwap = () =>
+      program.methods
+        .swapToPrices(
+          0,
+          [
+            new BN(((currentPrice * 10005n) / 10000n).toString()),
+            new BN(currentPrice.toString()),
+          ],
+          [new BN(0), new BN(0)],
+          [new BN(0), new BN(0)],
+          [],
+          [],
+          null,
+          null
+        )
+        .accounts(pdaSwapAccounts(pool, { user: operator.publicKey }))
+        .remainingAccounts(tickArrayAccounts(pool))
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ])
+        .signers([operator])
+        .rpc();
+
+    // The ledger is sized up front, so the operator, which holds no SOL,
+    // never pays for the owner's account
+    const sizeBefore = (await provider.connection.getAccountInfo(tradingAccount))
+      .data.length;
+    await operatorSwap();
+    const sizeAfter = (await provider.connection.getAccountInfo(tradingAccount))
+      .data.length;
+    expect(sizeAfter).to.equal(sizeBefore);
+    expect(await provider.connection.getBalance(operator.publicKey)).to.equal(0);
+
+    // Withdraw derives the trading account from the signer, so the
+    // operator cannot reach the owner's account
+    try {
+      await program.methods
+        .withdraw(0, new BN(1))
+        .accounts({
+          user: operator.publicKey,
+          //@ts-ignore
+          tradingAccount,
+          userTokenAccount: userTokenAta,
+          pdaTokenAccount: pdaTokenAta,
+          mint: tokenMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+      expect.fail("operator withdraw should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+    }
+
+    await program.methods
+      .removeOperator(0, operator.publicKey)
+      .accounts({ user: user.publicKey })
+      .rpc();
+
+    try {
+      await operatorSwap();
+      expect.fail("swap by a removed operator should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("Unauthorized");
+    }
+  });
+
+  it("Lets an operator swap until it expires", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const currentPrice = pool.currentSqrtPrice;
+
+    // Expiries are checked against the validator clock, not the host's
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+
+    const swapAs = (operator: Keypair) =>
+      program.methods
+        .swapToPrices(
+          0,
+          [
+            new BN(((currentPrice * 10005n) / 10000n).toString()),
+            new BN(currentPrice.toString()),
+          ],
+          [new BN(0), new BN(0)],
+          [new BN(0), new BN(0)],
+          [],
+          [],
+          null,
+          null
+        )
+        .accounts(pdaSwapAccounts(pool, { user: operator.publicKey }))
+        .remainingAccounts(tickArrayAccounts(pool))
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ])
+        .signers([operator])
+        .rpc();
+
+    // Still an hour to go
+    const active = Keypair.generate();
+    await program.methods
+      .addOperator(0, active.publicKey, new BN((await clusterTime()) + 3600))
+      .accounts({ user: user.publicKey })
+      .rpc();
+    await swapAs(active);
+
+    // Expires in a few seconds, used once the cluster is past that
+    const expiring = Keypair.generate();
+    const expiresAt = (await clusterTime()) + 3;
+    await program.methods
+      .addOperator(0, expiring.publicKey, new BN(expiresAt))
+      .accounts({ user: user.publicKey })
+      .rpc();
+    while ((await clusterTime()) <= expiresAt) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+
+    try {
+      await swapAs(expiring);
+      expect.fail("swap by an expired operator should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("Unauthorized");
+    } finally {
+      for (const operator of [active, expiring]) {
+        await program.methods
+          .removeOperator(0, operator.publicKey)
+          .accounts({ user: user.publicKey })
+          .rpc();
+      }
+    }
+  });
+
+  it("Swap to target prices (simple - no PDA)", async () => {
     const pool = await fetchPoolAccounts(POOL_ADDRESS);
 
     // Move price down 0.1%, then up 0.1%, then back to original
@@ -417,39 +1199,750 @@ describe("tag-the-chart-program", () => {
     const minOutputs = [new BN(0), new BN(0), new BN(0)];
 
     const tx = await program.methods
-      .swapToPricesSimple(targetSqrtPrices, maxInputs, minOutputs)
-      .accounts({
-        wallet: user.publicKey,
-        splAta: userTokenAta,
-        wsolAta: userWsolAta,
-        //@ts-ignore
-        raydiumProgram: RAYDIUM_CLMM_PROGRAM_ID,
-        ammConfig: pool.ammConfig,
-        poolState: pool.poolAddress,
-        tokenVaultA: pool.tokenVaultA,
-        tokenVaultB: pool.tokenVaultB,
-        tokenMintA: pool.tokenMintA,
-        tokenMintB: pool.tokenMintB,
-        observationState: pool.observationState,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
-        memoProgram: MEMO_PROGRAM_ID,
-        associatedTokenProgram: new PublicKey(
-          "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
-        ),
-        systemProgram: SystemProgram.programId,
-      })
-      .remainingAccounts(
-        pool.tickArrays.map((tickArray) => ({
-          pubkey: tickArray,
-          isWritable: true,
-          isSigner: false,
-        }))
+      .swapToPricesSimple(
+        targetSqrtPrices,
+        maxInputs,
+        minOutputs,
+        [],
+        [],
+        false,
+        null,
+        null
       )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
       .preInstructions([computeBudgetIx])
       .rpc();
 
     console.log("  ✅ Executed candle pattern swap (simple - no PDA)");
     console.log("  Transaction signature:", tx);
   });
+
+  it("Quotes enough input to reach a target across initialized ticks", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const open = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+
+    // 0.3% either side of the open crosses initialized ticks of the forked
+    // pool, with liquidity in range on both sides of each target. A zero
+    // tolerance fails with PriceNotReached unless the quoted input walks
+    // every tick on the way.
+    const priceDown = (open.sqrtPriceX64 * 997n) / 1000n;
+    const priceUp = (open.sqrtPriceX64 * 1003n) / 1000n;
+
+    await program.methods
+      .swapToPricesSimple(
+        [
+          new BN(priceDown.toString()),
+          new BN(priceUp.toString()),
+          new BN(open.sqrtPriceX64.toString()),
+        ],
+        [new BN(0), new BN(0), new BN(0)],
+        [new BN(0), new BN(0), new BN(0)],
+        [0, 0, 0],
+        [],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 }),
+      ])
+      .rpc();
+
+    const close = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+    expect(close.sqrtPriceX64).to.equal(open.sqrtPriceX64);
+  });
+
+  it("Emits the candle summary of a multi-step path", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const open = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+    const tokenIsA = pool.tokenMintA.equals(tokenMint);
+
+    const tokenBefore = await provider.connection.getTokenAccountBalance(
+      userTokenAta
+    );
+
+    const priceDown = (open.sqrtPriceX64 * 999n) / 1000n;
+    const priceUp = (open.sqrtPriceX64 * 1001n) / 1000n;
+    const signature = await program.methods
+      .swapToPricesSimple(
+        [
+          new BN(priceDown.toString()),
+          new BN(priceUp.toString()),
+          new BN(open.sqrtPriceX64.toString()),
+        ],
+        [new BN(0), new BN(0), new BN(0)],
+        [new BN(0), new BN(0), new BN(0)],
+        [],
+        [],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(tx.meta.logMessages)];
+    const candle = events.find((e) => e.name === "candleExecuted").data;
+
+    // Open, then one reached price per step; the path stays within its
+    // first two targets
+    const reached = candle.reachedSqrtPrices.map((p) => BigInt(p.toString()));
+    expect(BigInt(candle.openSqrtPrice.toString())).to.equal(open.sqrtPriceX64);
+    expect(reached).to.have.length(3);
+    expect(BigInt(candle.lowSqrtPrice.toString())).to.equal(reached[0]);
+    expect(BigInt(candle.highSqrtPrice.toString())).to.equal(reached[1]);
+    expect(reached[2]).to.equal(open.sqrtPriceX64);
+    expect(candle.signer.equals(user.publicKey)).to.be.true;
+    expect(candle.pool.equals(POOL_ADDRESS)).to.be.true;
+
+    // Steps 1 and 3 sell A, step 2 sells B; fees are part of the input
+    for (const [totalIn, totalOut, fee] of [
+      [candle.totalInA, candle.totalOutA, candle.estimatedFeeA],
+      [candle.totalInB, candle.totalOutB, candle.estimatedFeeB],
+    ]) {
+      expect(totalIn.gtn(0)).to.be.true;
+      expect(totalOut.gtn(0)).to.be.true;
+      expect(fee.gtn(0)).to.be.true;
+      expect(fee.lt(totalIn)).to.be.true;
+    }
+
+    // The token side moved exactly by what the candle reports
+    const tokenAfter = await provider.connection.getTokenAccountBalance(
+      userTokenAta
+    );
+    const [tokenIn, tokenOut] = tokenIsA
+      ? [candle.totalInA, candle.totalOutA]
+      : [candle.totalInB, candle.totalOutB];
+    expect(
+      new BN(tokenAfter.value.amount)
+        .sub(new BN(tokenBefore.value.amount))
+        .toString()
+    ).to.equal(tokenOut.sub(tokenIn).toString());
+  });
+
+  it("Buys an exact output amount bounded by a price limit", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // Price up means buying token A; never let it move more than +0.1%
+    const priceLimit = (pool.currentSqrtPrice * 1001n) / 1000n;
+    const exactOut = new BN(1_000);
+
+    const balanceBefore = await provider.connection.getTokenAccountBalance(
+      userAtaA
+    );
+
+    await program.methods
+      .swapToPricesSimple(
+        [new BN(priceLimit.toString())],
+        [new BN(0)],
+        [exactOut],
+        [],
+        [true],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .rpc();
+
+    const balanceAfter = await provider.connection.getTokenAccountBalance(
+      userAtaA
+    );
+    expect(
+      new BN(balanceAfter.value.amount).sub(new BN(balanceBefore.value.amount))
+        .toNumber()
+    ).to.be.at.least(exactOut.toNumber());
+  });
+
+  it("Unwraps leftover WSOL back to the wallet", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const wsolAta = pool.tokenMintA.equals(WSOL_MINT) ? userAtaA : userAtaB;
+
+    // Run a down-and-back path with `unwrap_sol` and check that SolSettled
+    // accounts for every lamport the wallet gained or lost
+    const swapAndUnwrap = async () => {
+      const ataBefore = await provider.connection.getAccountInfo(wsolAta);
+      const heldBefore = ataBefore
+        ? new BN(
+            (
+              await provider.connection.getTokenAccountBalance(wsolAta)
+            ).value.amount
+          )
+        : new BN(0);
+      // Rent of an existing ATA comes back on close; one created by the
+      // swap is paid and refunded within it
+      const rent = ataBefore
+        ? new BN(ataBefore.lamports).sub(heldBefore)
+        : new BN(0);
+      const lamportsBefore = await provider.connection.getBalance(
+        user.publicKey
+      );
+
+      const currentPrice = pool.currentSqrtPrice;
+      const signature = await program.methods
+        .swapToPricesSimple(
+          [
+            new BN(((currentPrice * 999n) / 1000n).toString()),
+            new BN(currentPrice.toString()),
+          ],
+          [new BN(0), new BN(0)],
+          [new BN(0), new BN(0)],
+          [],
+          [],
+          true,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .remainingAccounts(tickArrayAccounts(pool))
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ])
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const events = [...parser.parseLogs(tx.meta.logMessages)];
+      const settled = events.find((e) => e.name === "solSettled").data;
+
+      // The WSOL ATA is closed, so all of its balance is native SOL again
+      expect(await provider.connection.getAccountInfo(wsolAta)).to.be.null;
+      expect(settled.heldBefore.toString()).to.equal(heldBefore.toString());
+      expect(settled.returned.toString()).to.equal(
+        heldBefore.add(settled.wrapped).sub(settled.spent).toString()
+      );
+
+      const lamportsAfter = await provider.connection.getBalance(
+        user.publicKey
+      );
+      const expectedDelta = settled.returned
+        .sub(settled.wrapped)
+        .add(rent)
+        .subn(tx.meta.fee);
+      expect(lamportsAfter - lamportsBefore).to.equal(
+        expectedDelta.toNumber()
+      );
+
+      return settled;
+    };
+
+    // The ATA still holds WSOL wrapped during setup: it is unwrapped too
+    expect(await provider.connection.getAccountInfo(wsolAta)).to.not.be.null;
+    const preHeld = await swapAndUnwrap();
+    expect(preHeld.heldBefore.gtn(0)).to.be.true;
+    expect(preHeld.returned.gt(preHeld.wrapped.sub(preHeld.spent))).to.be.true;
+
+    // Without a prior balance only what the path left over comes back
+    const fresh = await swapAndUnwrap();
+    expect(fresh.heldBefore.toString()).to.equal("0");
+    expect(fresh.returned.toString()).to.equal(
+      fresh.wrapped.sub(fresh.spent).toString()
+    );
+  });
+
+  it("Swaps to offsets from the open price and pins the close", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const open = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+
+    // Down 10 bps selling A, up 10 bps selling B, back to the open selling A
+    const holdingA = await walletHolding(pool.tokenMintA, userAtaA);
+    const holdingB = await walletHolding(pool.tokenMintB, userAtaB);
+
+    // The last offset is overridden by pinning the close to the open
+    await program.methods
+      .swapToOffsetsSimple(
+        { offsetsBps: [-10, 10, 25], pinCloseToOpen: true },
+        [holdingA, holdingB, holdingA],
+        [new BN(1), new BN(1), new BN(1)],
+        [],
+        [],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .rpc();
+
+    const close = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+    expect(close.sqrtPriceX64).to.equal(open.sqrtPriceX64);
+  });
+
+  it("Rejects a path with mismatched argument lengths", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    const currentPrice = pool.currentSqrtPrice;
+    const targetSqrtPrices = [
+      new BN(((currentPrice * 999n) / 1000n).toString()),
+      new BN(currentPrice.toString()),
+    ];
+
+    // One max input for two steps
+    const maxInputs = [new BN(0)];
+    const minOutputs = [new BN(0), new BN(0)];
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          targetSqrtPrices,
+          maxInputs,
+          minOutputs,
+          [],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap with mismatched path lengths should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("PathLengthMismatch");
+    }
+  });
+
+  it("Rejects a target sqrt price outside Raydium's range", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // The largest u128 would overflow the price-reached tolerance
+    for (const target of [new BN(0), new BN(2).pow(new BN(128)).subn(1)]) {
+      try {
+        await program.methods
+          .swapToPricesSimple(
+            [target],
+            [new BN(0)],
+            [new BN(0)],
+            [],
+            [],
+            false,
+            null,
+            null
+          )
+          .accounts(simpleSwapAccounts(pool))
+          .rpc();
+        expect.fail("swap to an out-of-range sqrt price should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("PriceOutOfRange");
+      }
+    }
+  });
+
+  it("Rejects a decimal target price of zero", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    try {
+      await program.methods
+        .swapToDecimalPricesSimple(
+          { prices: [new BN(0)], exponent: 2, baseIsA: true },
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap with a zero decimal price should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("PriceOutOfRange");
+    }
+  });
+
+  it("Swaps to decimal prices around the open price", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const local = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+
+    // Human price of A in B, scaled up until it has 10 significant digits
+    const scaledPrice = (exponent: number) =>
+      (local.sqrtPriceX64 *
+        local.sqrtPriceX64 *
+        10n ** BigInt(exponent + local.mintDecimalsA)) /
+      ((1n << 128n) * 10n ** BigInt(local.mintDecimalsB));
+    let exponent = 0;
+    while (scaledPrice(exponent) < 1_000_000_000n) {
+      exponent += 1;
+    }
+    const openPrice = scaledPrice(exponent);
+
+    // Down 10 bps selling A, then back to the open selling B, spending at
+    // most what the wallet holds and always receiving something
+    await program.methods
+      .swapToDecimalPricesSimple(
+        {
+          prices: [
+            new BN(((openPrice * 9990n) / 10000n).toString()),
+            new BN(openPrice.toString()),
+          ],
+          exponent,
+          baseIsA: true,
+        },
+        [
+          await walletHolding(pool.tokenMintA, userAtaA),
+          await walletHolding(pool.tokenMintB, userAtaB),
+        ],
+        [new BN(1), new BN(1)],
+        [],
+        [],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .rpc();
+
+    // The close lands back on the open, up to the decimal rounding
+    const { sqrtPriceX64 } = await fetchLocalPoolState(
+      provider.connection,
+      POOL_ADDRESS
+    );
+    const drift = sqrtPriceX64 - local.sqrtPriceX64;
+    const driftBps = ((drift < 0n ? -drift : drift) * 10000n) / sqrtPriceX64;
+    expect(Number(driftBps)).to.be.at.most(5);
+  });
+
+  it("Swaps to exact initializable ticks", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const local = await fetchLocalPoolState(provider.connection, POOL_ADDRESS);
+
+    // One tick spacing below and above the spacing-aligned current tick
+    const aligned =
+      Math.floor(local.tickCurrent / local.tickSpacing) * local.tickSpacing;
+    const lowerTick = aligned - local.tickSpacing;
+    const upperTick = aligned + local.tickSpacing;
+
+    await program.methods
+      .swapToTicksSimple(
+        [lowerTick, upperTick],
+        [
+          await walletHolding(pool.tokenMintA, userAtaA),
+          await walletHolding(pool.tokenMintB, userAtaB),
+        ],
+        [new BN(1), new BN(1)],
+        [],
+        false,
+        null,
+        null
+      )
+      .accounts(simpleSwapAccounts(pool))
+      .remainingAccounts(tickArrayAccounts(pool))
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .rpc();
+
+    // Moving up stops exactly on the target tick's sqrt price
+    const { tickCurrent } = await fetchLocalPoolState(
+      provider.connection,
+      POOL_ADDRESS
+    );
+    expect(tickCurrent).to.equal(upperTick);
+  });
+
+  it("Rejects a tick target outside the tick range", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    try {
+      await program.methods
+        .swapToTicksSimple(
+          [500_000],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap to a tick beyond MAX_TICK should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("TickOutOfRange");
+    }
+  });
+
+  it("Aborts when the pool opened away from the expected price", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // Planned from a price 1% above the real open, with 10 bps of drift allowed
+    const expectedOpen = (pool.currentSqrtPrice * 101n) / 100n;
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          {
+            expectedSqrtPrice: new BN(expectedOpen.toString()),
+            maxDriftBps: 10,
+          },
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap from a drifted open price should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("OpenPriceDrift");
+    }
+  });
+
+  it("Rejects open price guards it cannot evaluate", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    const swapWithGuard = (expectedSqrtPrice: BN, maxDriftBps: number) =>
+      program.methods
+        .swapToPricesSimple(
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          { expectedSqrtPrice, maxDriftBps },
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+
+    try {
+      await swapWithGuard(new BN(pool.currentSqrtPrice.toString()), 10_001);
+      expect.fail("drift above 10000 bps should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidTolerance");
+    }
+
+    // Scaling the expected price by the drift overflows u128
+    try {
+      await swapWithGuard(new BN(2).pow(new BN(128)).subn(1), 10);
+      expect.fail("an overflowing expected price should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("Overflow");
+    }
+  });
+
+  it("Reverts a path that costs more than the net spend cap", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // A round trip back to the open still pays trading fees on both sides
+    const currentPrice = pool.currentSqrtPrice;
+    const targetSqrtPrices = [
+      new BN(((currentPrice * 999n) / 1000n).toString()),
+      new BN(currentPrice.toString()),
+    ];
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          targetSqrtPrices,
+          [new BN(0), new BN(0)],
+          [new BN(0), new BN(0)],
+          [],
+          [],
+          false,
+          null,
+          {
+            baseIsA: true,
+            maxNetBaseSpent: new BN(1),
+            maxNetQuoteSpent: new BN(1),
+          }
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .remainingAccounts(tickArrayAccounts(pool))
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ])
+        .rpc();
+      expect.fail("round trip above the net spend cap should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("NetSpendExceeded");
+    }
+  });
+
+  it("Rejects a swap on a pool that is not allowlisted", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    await program.methods
+      .removePool(POOL_ADDRESS)
+      .accounts({ admin: user.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(simpleSwapAccounts(pool))
+        .rpc();
+      expect.fail("swap on a pool removed from the allowlist should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("PoolNotAllowed");
+    } finally {
+      await program.methods
+        .addPool()
+        .accounts({ admin: user.publicKey, poolState: POOL_ADDRESS })
+        .rpc();
+    }
+  });
+
+  it("Rejects swaps while paused but still allows withdrawals", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    await program.methods
+      .setPaused(true)
+      .accounts({ authority: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+
+    try {
+      try {
+        await program.methods
+          .swapToPricesSimple(
+            [new BN(pool.currentSqrtPrice.toString())],
+            [new BN(0)],
+            [new BN(0)],
+            [],
+            [],
+            false,
+            null,
+            null
+          )
+          .accounts(simpleSwapAccounts(pool))
+          .rpc();
+        expect.fail("swap while paused should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ProgramPaused");
+      }
+
+      const pdaTokenBefore = await provider.connection.getTokenAccountBalance(
+        pdaTokenAta
+      );
+      await program.methods
+        .withdraw(0, new BN(1))
+        .accounts({
+          userTokenAccount: userTokenAta,
+          pdaTokenAccount: pdaTokenAta,
+          mint: tokenMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const pdaTokenAfter = await provider.connection.getTokenAccountBalance(
+        pdaTokenAta
+      );
+      expect(
+        new BN(pdaTokenBefore.value.amount)
+          .sub(new BN(pdaTokenAfter.value.amount))
+          .toNumber()
+      ).to.equal(1);
+    } finally {
+      await program.methods
+        .setPaused(false)
+        .accounts({ authority: user.publicKey })
+        .rpc();
+    }
+  });
+
+  it("Rejects a swap whose vaults do not match the pool", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    try {
+      await program.methods
+        .swapToPricesSimple(
+          [new BN(pool.currentSqrtPrice.toString())],
+          [new BN(0)],
+          [new BN(0)],
+          [],
+          [],
+          false,
+          null,
+          null
+        )
+        .accounts(
+          simpleSwapAccounts(pool, {
+            // Vaults swapped
+            tokenVaultA: pool.tokenVaultB,
+            tokenVaultB: pool.tokenVaultA,
+          })
+        )
+        .rpc();
+      expect.fail("swap with mismatched pool vaults should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidTokenVault");
+    }
+  });
+
+  it("Rejects a swap with another pool's AMM config", async () => {
+    // A real AMM config, cloned from mainnet, with another fee tier
+    const [otherAmmConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("amm_config"), Buffer.from([0, 0])],
+      RAYDIUM_CLMM_PROGRAM_ID
+    );
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+    expect(otherAmmConfig.equals(pool.ammConfig)).to.be.false;
+
+    await expectSimpleSwapError(
+      { ammConfig: otherAmmConfig },
+      "InvalidAmmConfig"
+    );
+  });
+
+  it("Rejects a swap whose mints do not match the pool", async () => {
+    const pool = await fetchPoolAccounts(POOL_ADDRESS);
+
+    // Mints swapped
+    await expectSimpleSwapError(
+      { tokenMintA: pool.tokenMintB, tokenMintB: pool.tokenMintA },
+      "InvalidPoolMint"
+    );
+
+    // A mint foreign to the pool
+    const foreignMint = await createMint(
+      provider.connection,
+      user,
+      user.publicKey,
+      null,
+      6
+    );
+    await expectSimpleSwapError({ tokenMintB: foreignMint }, "InvalidPoolMint");
+  });
+
+  it("Rejects a swap with another observation account", async () => {
+    await expectSimpleSwapError(
+      { observationState: Keypair.generate().publicKey },
+      "InvalidObservationState"
+    );
+  });
 });
@@ -0,0 +1,21 @@
+import { PublicKey } from "@solana/web3.js";
+
+/**
+ * Address and bump of `owner`'s trading account `id`
+ *
+ * Account 0 uses no id seed, so it keeps the original
+ * ["trading_account", owner] address.
+ */
+export function tradingAccountPda(
+  programId: PublicKey,
+  owner: PublicKey,
+  id: number
+): [PublicKey, number] {
+  const seeds = [Buffer.from("trading_account"), owner.toBuffer()];
+  if (id !== 0) {
+    const idSeed = Buffer.alloc(2);
+    idSeed.writeUInt16LE(id);
+    seeds.push(idSeed);
+  }
+  return PublicKey.findProgramAddressSync(seeds, programId);
+}
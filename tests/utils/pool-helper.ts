@@ -55,3 +55,46 @@ export async function fetchPoolAccounts(
 
   return accounts;
 }
+
+export interface LocalPoolState {
+  mintDecimalsA: number;
+  mintDecimalsB: number;
+  tickSpacing: number;
+  sqrtPriceX64: bigint;
+  tickCurrent: number;
+}
+
+// Offsets in Raydium's packed PoolState: discriminator, bump, then seven
+// pubkeys (config, owner, mints, vaults, observation)
+const MINT_DECIMALS_OFFSET = 8 + 1 + 7 * 32;
+const TICK_SPACING_OFFSET = MINT_DECIMALS_OFFSET + 2;
+const SQRT_PRICE_OFFSET = TICK_SPACING_OFFSET + 2 + 16;
+const TICK_CURRENT_OFFSET = SQRT_PRICE_OFFSET + 16;
+
+/**
+ * Read the pool state of the local (forked) validator
+ *
+ * The mainnet price keeps moving after the fork, so tests asserting on where
+ * a swap left the pool must read the price from the local copy.
+ */
+export async function fetchLocalPoolState(
+  connection: Connection,
+  poolAddress: PublicKey
+): Promise<LocalPoolState> {
+  const account = await connection.getAccountInfo(poolAddress);
+  if (!account) {
+    throw new Error(`Pool ${poolAddress.toBase58()} not found`);
+  }
+  const data = account.data;
+
+  const low = data.readBigUInt64LE(SQRT_PRICE_OFFSET);
+  const high = data.readBigUInt64LE(SQRT_PRICE_OFFSET + 8);
+
+  return {
+    mintDecimalsA: data.readUInt8(MINT_DECIMALS_OFFSET),
+    mintDecimalsB: data.readUInt8(MINT_DECIMALS_OFFSET + 1),
+    tickSpacing: data.readUInt16LE(TICK_SPACING_OFFSET),
+    sqrtPriceX64: (high << 64n) | low,
+    tickCurrent: data.readInt32LE(TICK_CURRENT_OFFSET),
+  };
+}